@@ -8,15 +8,24 @@ use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use colored::*;
 use hpd_rust_agent::{
-    AgentBuilder, 
-    Conversation, 
+    Agent,
+    AgentBuilder,
+    ChatProvider,
+    Conversation,
+    FunctionCallRequest,
+    StreamEvent,
     AppSettings,
     PluginRegistration,
     register_plugin,
     get_registered_plugins,
+    get_plugin_stats,
+    list_functions,
     hpd_plugin,
     ai_function,
 };
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::{Context as RlContext, Editor};
 use std::io::{self, Write};
 use std::collections::HashMap;
 
@@ -141,6 +150,8 @@ fn register_console_plugin() -> Result<()> {
             ("echo_styled".to_string(), "echo_styled_wrapper".to_string()),
         ],
         schemas: HashMap::new(), // Would normally contain JSON schemas
+        is_unique: true,
+        examples: HashMap::new(),
     };
     
     register_plugin(plugin);
@@ -149,18 +160,122 @@ fn register_console_plugin() -> Result<()> {
     Ok(())
 }
 
+/// The slash commands `ReplHelper` completes and the main loop dispatches.
+const SLASH_COMMANDS: &[&str] = &["/plugins", "/model", "/clear", "/save", "/help", "/quit", "/exit"];
+
+/// Where persistent REPL input history is read from and written back to.
+const HISTORY_FILE: &str = ".hpd_console_history";
+
+/// Tab-completes slash commands and registered plugin function names. The
+/// other `rustyline::Helper` traits (hinting, highlighting, validation) are
+/// left at their default no-op implementations - this REPL only needs
+/// completion.
+struct ReplHelper {
+    candidates: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &RlContext<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(|c: char| c.is_whitespace())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &line[start..pos];
+
+        let matches = self
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .cloned()
+            .collect();
+
+        Ok((start, matches))
+    }
+}
+
+impl rustyline::hint::Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl rustyline::highlight::Highlighter for ReplHelper {}
+impl rustyline::validate::Validator for ReplHelper {}
+impl rustyline::Helper for ReplHelper {}
+
+/// Prompts for a y/n confirmation before a side-effecting function call is
+/// dispatched. Shared by the initial agent build and any `/model` rebuild so
+/// switching models mid-session doesn't change confirmation behavior.
+fn confirm_call(call: &FunctionCallRequest) -> bool {
+    print!(
+        "\n{} Allow call to '{}' with arguments {}? [y/N] ",
+        "⚠️".yellow(),
+        call.name,
+        call.arguments
+    );
+    io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    answer.trim().eq_ignore_ascii_case("y")
+}
+
+/// Builds the interactive chat agent for `model`, wiring up the same
+/// instructions, call budget, fallback, and confirmation behavior regardless
+/// of whether this is the initial build or a `/model`-triggered rebuild.
+fn build_interactive_agent(
+    agent_name: &str,
+    agent_instructions: &str,
+    max_calls: i32,
+    model: &str,
+    api_key: &str,
+    config: &AppSettings,
+) -> std::result::Result<Agent, hpd_rust_agent::AgentError> {
+    let mut builder = AgentBuilder::new(agent_name)
+        .with_instructions(agent_instructions)
+        .with_max_function_calls(max_calls)
+        .with_max_conversation_history(50)
+        .with_openrouter_full(model, api_key, Some(config.get_openrouter_base_url().to_string()));
+
+    // When appsettings.json has both a fallback model and an OpenAI key
+    // configured, the conversation degrades to it automatically instead of
+    // erroring out on a rate limit or outage of the primary OpenRouter model.
+    if let (Some(fallback_model), Some(openai_key)) = (config.get_fallback_model(), config.get_openai_api_key()) {
+        builder = builder.with_fallback_model(ChatProvider::OpenAI, fallback_model, Some(openai_key.to_string()));
+    }
+
+    builder.with_confirmation_callback(confirm_call).build()
+}
+
+fn print_repl_help() {
+    println!("{} Slash commands:", "ℹ️".blue());
+    println!("   /plugins        Show registered plugins and call statistics");
+    println!("   /model <name>   Switch the active model (keeps conversation history)");
+    println!("   /clear          Clear conversation history");
+    println!("   /save <file>    Save the conversation transcript to <file>");
+    println!("   /help           Show this message");
+    println!("   quit, exit      End the conversation");
+}
+
 async fn run_interactive_chat(agent_name: &str, instructions: Option<String>, max_calls: u32) -> Result<()> {
     println!("{} Starting interactive chat mode...", "💬".blue());
-    
+
     // Load configuration
     let config = AppSettings::load()
         .map_err(|e| anyhow::anyhow!("Failed to load configuration: {}", e))?;
-    
+
     let api_key = config.get_openrouter_api_key()
         .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not found. Please add it to appsettings.json"))?;
-    
+
     let model = config.get_default_model().unwrap_or("google/gemini-2.5-pro");
-    
+
     // Default instructions
     let default_instructions = format!(
         "You are {}, a helpful AI assistant with access to console utilities. \
@@ -168,60 +283,162 @@ async fn run_interactive_chat(agent_name: &str, instructions: Option<String>, ma
          Be friendly and demonstrate your capabilities when appropriate.",
         agent_name
     );
-    
+
     let agent_instructions = instructions.as_deref().unwrap_or(&default_instructions);
-    
+
     println!("{} Creating agent: {}", "🤖".yellow(), agent_name.bold());
     println!("   Instructions: {}", agent_instructions);
     println!("   Model: {}", model);
-    
-    // Create agent
-    let agent = AgentBuilder::new(agent_name)
-        .with_instructions(agent_instructions)
-        .with_max_function_calls(max_calls as i32)
-        .with_max_conversation_history(50)
-        .with_openrouter(model, api_key)
-        .build()
+    if let Some(fallback_model) = config.get_fallback_model() {
+        if config.get_openai_api_key().is_some() {
+            println!("   Fallback model: {} (OpenAI)", fallback_model);
+        }
+    }
+
+    // Create agent. Side-effecting calls (the `may_`/`execute_` naming
+    // convention `is_side_effecting` checks for) prompt here for a y/n
+    // confirmation before the conversation loop dispatches them, instead of
+    // auto-approving like the non-interactive demo modes do.
+    let agent = build_interactive_agent(agent_name, agent_instructions, max_calls as i32, model, api_key, &config)
         .map_err(|e| anyhow::anyhow!("Failed to create agent: {}", e))?;
-    
+
     // Create conversation
     let conversation = Conversation::new(vec![agent])
         .map_err(|e| anyhow::anyhow!("Failed to create conversation: {}", e))?;
-    
-    println!("\n{} Chat started! Type 'quit' or 'exit' to end the conversation.", "🎉".green());
+
+    // Tab-completion covers the fixed slash commands plus every function
+    // name a plugin has registered, so users can discover what's callable
+    // without leaving the prompt.
+    let candidates: Vec<String> = SLASH_COMMANDS
+        .iter()
+        .map(|s| s.to_string())
+        .chain(list_functions())
+        .collect();
+
+    let mut rl: Editor<ReplHelper, rustyline::history::DefaultHistory> = Editor::new()
+        .map_err(|e| anyhow::anyhow!("Failed to create line editor: {}", e))?;
+    rl.set_helper(Some(ReplHelper { candidates }));
+    rl.load_history(HISTORY_FILE).ok();
+
+    println!("\n{} Chat started! Type 'quit' or 'exit' to end the conversation, or /help for commands.", "🎉".green());
     println!("{}", "─".repeat(60).dimmed());
-    
+
     // Interactive loop
     loop {
-        print!("\n{} ", "You:".bold().blue());
-        io::stdout().flush().unwrap();
-        
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        let readline = rl.readline(&format!("\n{} ", "You:".bold().blue()));
+
+        let input = match readline {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => {
+                println!("\n{} Goodbye! Thanks for using HPD Console App!", "👋".yellow());
+                break;
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to read input: {}", e)),
+        };
         let input = input.trim();
-        
+
         if input.is_empty() {
             continue;
         }
-        
+        rl.add_history_entry(input).ok();
+
         if input.eq_ignore_ascii_case("quit") || input.eq_ignore_ascii_case("exit") {
             println!("\n{} Goodbye! Thanks for using HPD Console App!", "👋".yellow());
             break;
         }
-        
+
+        if let Some(rest) = input.strip_prefix('/') {
+            let mut parts = rest.splitn(2, char::is_whitespace);
+            let command = parts.next().unwrap_or("");
+            let argument = parts.next().map(str::trim).unwrap_or("");
+
+            match command {
+                "help" => print_repl_help(),
+                "plugins" => show_plugin_info(),
+                "clear" => match conversation.clear_history() {
+                    Ok(()) => println!("{} Conversation history cleared.", "🧹".yellow()),
+                    Err(e) => println!("{} {}", "❌".red(), e),
+                },
+                "save" => {
+                    if argument.is_empty() {
+                        println!("{} Usage: /save <file>", "⚠️".yellow());
+                    } else {
+                        match conversation.history_snapshot() {
+                            Ok(history) => {
+                                let transcript = history
+                                    .iter()
+                                    .map(|m| format!("{:?}: {}", m.role, m.content))
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                                match std::fs::write(argument, transcript) {
+                                    Ok(()) => println!("{} Transcript saved to {}", "💾".green(), argument),
+                                    Err(e) => println!("{} Failed to save transcript: {}", "❌".red(), e),
+                                }
+                            }
+                            Err(e) => println!("{} {}", "❌".red(), e),
+                        }
+                    }
+                }
+                "model" => {
+                    if argument.is_empty() {
+                        println!("{} Usage: /model <name>", "⚠️".yellow());
+                    } else {
+                        match build_interactive_agent(agent_name, agent_instructions, max_calls as i32, argument, api_key, &config) {
+                            Ok(new_agent) => match conversation.set_agent(new_agent) {
+                                Ok(()) => println!("{} Switched to model: {}", "🔄".green(), argument),
+                                Err(e) => println!("{} {}", "❌".red(), e),
+                            },
+                            Err(e) => println!("{} Failed to switch model: {}", "❌".red(), e),
+                        }
+                    }
+                }
+                "quit" | "exit" => {
+                    println!("\n{} Goodbye! Thanks for using HPD Console App!", "👋".yellow());
+                    break;
+                }
+                other => println!("{} Unknown command: /{}. Type /help for a list.", "⚠️".yellow(), other),
+            }
+            continue;
+        }
+
         print!("{} ", "Assistant:".bold().green());
         io::stdout().flush().unwrap();
-        
-        match conversation.send(input) {
-            Ok(response) => {
-                println!("{}", response);
+
+        match conversation.send_stream(input) {
+            Ok(mut events) => {
+                let mut printed_anything = false;
+                while let Some(event) = events.recv().await {
+                    match event {
+                        StreamEvent::TokenDelta(delta) => {
+                            print!("{}", delta);
+                            io::stdout().flush().ok();
+                            printed_anything = true;
+                        }
+                        StreamEvent::ToolCallStarted { name, .. } => {
+                            println!("\n{} Calling '{}'...", "🔧".cyan(), name);
+                        }
+                        StreamEvent::ToolResult { name, result: Err(error), .. } => {
+                            println!("{} '{}' failed: {}", "❌".red(), name, error.red());
+                        }
+                        StreamEvent::ToolResult { .. } => {}
+                        StreamEvent::Error(error) => {
+                            println!("{} Error: {}", "❌".red(), error.red());
+                        }
+                        StreamEvent::Done => {}
+                    }
+                }
+                if !printed_anything {
+                    println!();
+                }
             }
             Err(e) => {
                 println!("{} Error: {}", "❌".red(), e.to_string().red());
             }
         }
     }
-    
+
+    rl.save_history(HISTORY_FILE).ok();
+
     Ok(())
 }
 
@@ -233,7 +450,14 @@ async fn run_basic_tests() -> Result<()> {
     let config = AppSettings::load()
         .map_err(|e| anyhow::anyhow!("Configuration test failed: {}", e))?;
     println!("   ✅ Configuration loaded successfully");
-    
+
+    if config.get_openrouter_api_key().is_some() {
+        println!("      OpenRouter endpoint: {}", config.get_openrouter_base_url());
+    }
+    if config.get_openai_api_key().is_some() {
+        println!("      OpenAI endpoint: {}", config.get_openai_base_url());
+    }
+
     // Test 2: Plugin registration
     println!("\n{} Test 2: Plugin System", "2️⃣".blue());
     let plugins = get_registered_plugins();
@@ -294,10 +518,21 @@ fn show_plugin_info() {
         println!("     Functions: {}", plugin.functions.len());
     }
     
-    // TODO: Add plugin stats if available
-    // let stats = get_plugin_stats();
-    // println!("\nPlugin Statistics:");
-    // println!("  Total function calls: {}", stats.total_calls);
+    let stats = get_plugin_stats();
+    println!("\nPlugin Statistics:");
+    if let Some(plugins) = stats["plugins"].as_array() {
+        for plugin in plugins {
+            println!(
+                "  {}: {} call(s)",
+                plugin["name"].as_str().unwrap_or("?"),
+                plugin["call_count"].as_u64().unwrap_or(0)
+            );
+        }
+    }
+    println!(
+        "  Total concurrent executions: {}",
+        stats["total_concurrent_executions"].as_u64().unwrap_or(0)
+    );
 }
 
 async fn run_demo() -> Result<()> {
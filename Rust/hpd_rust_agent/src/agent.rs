@@ -1,9 +1,11 @@
-use serde::Serialize;
-use std::ffi::{CString, c_void};
+use serde::{Deserialize, Serialize};
+use std::ffi::c_void;
 use std::collections::HashMap;
-use crate::ffi;
+use std::sync::{Arc, Mutex};
 use crate::plugins::PluginRegistration;
 use crate::plugin_context::PluginConfiguration;
+use crate::ffi_backend::{CSharpBackend, FfiBackend};
+use crate::conversation::FunctionCallRequest;
 
 /// Trait that all plugins must implement
 /// This is implemented automatically by the #[hpd_plugin] macro
@@ -33,21 +35,26 @@ impl From<&PluginRegistration> for Vec<RustFunctionInfo> {
             let schema = plugin.schemas.get(name)
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| "{}".to_string());
-            
+
+            // A `may_`- or `execute_`-prefixed function is side-effecting (see
+            // `is_side_effecting`) and requires permission to run; anything
+            // else is read-only and always auto-invoked.
+            let requires_permission = crate::conversation::is_side_effecting(name);
+
             RustFunctionInfo {
                 name: name.to_string(),
                 description: format!("Function: {}", name),
                 wrapper_function_name: wrapper.to_string(),
                 schema,
-                requires_permission: false, // TODO: Parse from plugin metadata
-                required_permissions: vec![],
+                requires_permission,
+                required_permissions: if requires_permission { vec!["execute".to_string()] } else { vec![] },
             }
         }).collect()
     }
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
 pub struct AgentConfig {
     pub name: String,
     pub system_instructions: String,
@@ -64,19 +71,37 @@ pub struct AgentConfig {
     /// Key is plugin name, value contains dynamic context properties.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plugin_configurations: Option<HashMap<String, PluginConfiguration>>,
+
+    /// The [`WireEncoding`](crate::wire::WireEncoding) this agent negotiates
+    /// with the C# side for every later FFI payload, not just this config
+    /// itself. Defaults to `Json` for debuggability.
+    pub encoding: crate::wire::WireEncoding,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ProviderConfig {
     pub provider: ChatProvider,
     pub model_name: String,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
     pub api_key: Option<String>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
     pub endpoint: Option<String>,
     // DefaultChatOptions would be complex to serialize, so we'll skip it for now
 }
 
-#[derive(Serialize, Clone, Copy)]
+/// Deserializes an optional string field, treating an empty string the same
+/// as an absent one - a TOML/JSON config file with `api_key = ""` means "no
+/// key set", not "the key is the empty string".
+fn empty_string_as_none<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Option<String> = Option::deserialize(deserializer)?;
+    Ok(value.filter(|s| !s.is_empty()))
+}
+
+#[derive(Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(into = "u32")]
 #[repr(u32)]
 pub enum ChatProvider {
@@ -85,6 +110,7 @@ pub enum ChatProvider {
     OpenRouter = 2,
     AppleIntelligence = 3,
     Ollama = 4,
+    Anthropic = 5,
 }
 
 impl Into<u32> for ChatProvider {
@@ -93,6 +119,87 @@ impl Into<u32> for ChatProvider {
     }
 }
 
+impl ChatProvider {
+    /// Whether this provider supports model-driven function/tool calling at
+    /// all. `model_name` is accepted for forward compatibility (some
+    /// providers only gate support per-model) but isn't consulted yet: every
+    /// provider here supports tool calling uniformly except
+    /// `AppleIntelligence`, whose on-device model has no tool-calling API.
+    pub fn supports_function_calling(&self, _model_name: &str) -> bool {
+        !matches!(self, ChatProvider::AppleIntelligence)
+    }
+}
+
+/// Errors [`AgentBuilder::build`] can report that are worth matching on
+/// directly rather than parsing out of a generic `String`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentError {
+    /// Plugins were registered on this agent, but `provider` with `model`
+    /// has no function-calling support to invoke them with.
+    FunctionCallingUnsupported { provider: ChatProvider, model: String },
+    /// Anything else `build` can fail on (FFI/config encode failures, a
+    /// backend rejecting `create_agent`, ...) that isn't worth its own
+    /// variant yet.
+    Other(String),
+}
+
+impl std::fmt::Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgentError::FunctionCallingUnsupported { provider, model } => write!(
+                f,
+                "Provider {:?} with model '{}' does not support function calling, but plugins are registered on this agent",
+                provider, model
+            ),
+            AgentError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+impl From<String> for AgentError {
+    fn from(message: String) -> Self {
+        AgentError::Other(message)
+    }
+}
+
+/// Governs whether a side-effecting plugin function is allowed to run when
+/// the tool-calling loop wants to dispatch it. A function is treated as
+/// side-effecting by the `may_`/`execute_` name prefix convention (see
+/// [`is_side_effecting`](crate::conversation::is_side_effecting)); everything
+/// else dispatches automatically regardless of this policy.
+pub enum ConfirmationPolicy {
+    /// Run every side-effecting call without asking.
+    AutoApprove,
+    /// Refuse every side-effecting call outright.
+    Deny,
+    /// Ask a caller-supplied callback for each side-effecting call. The
+    /// callback sees the pending call (name and arguments) before it has
+    /// executed, not a [`ToolCall`](crate::conversation::ToolCall) trace
+    /// entry, since there's no outcome yet to report.
+    Callback(Mutex<Box<dyn FnMut(&FunctionCallRequest) -> bool + Send>>),
+}
+
+impl Default for ConfirmationPolicy {
+    fn default() -> Self {
+        ConfirmationPolicy::AutoApprove
+    }
+}
+
+impl ConfirmationPolicy {
+    pub(crate) fn approves(&self, call: &FunctionCallRequest) -> bool {
+        match self {
+            ConfirmationPolicy::AutoApprove => true,
+            ConfirmationPolicy::Deny => false,
+            ConfirmationPolicy::Callback(callback) => match callback.lock() {
+                Ok(mut callback) => (*callback)(call),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
@@ -102,19 +209,76 @@ impl Default for AgentConfig {
             max_conversation_history: 20,
             provider: None,
             plugin_configurations: None,
+            encoding: crate::wire::WireEncoding::Json,
         }
     }
 }
 
+impl AgentConfig {
+    /// Reads `path`, expands `${ENV_VAR}` placeholders against the process
+    /// environment, and parses the result as TOML (or JSON if `path` ends in
+    /// `.json`). Missing fields fall back to [`AgentConfig::default`] (see
+    /// the container-level `#[serde(default)]`), so a config file only
+    /// needs to spell out what it's overriding.
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read agent config '{}': {}", path, e))?;
+
+        if path.ends_with(".json") {
+            Self::from_json_str(&raw)
+        } else {
+            Self::from_toml_str(&raw)
+        }
+    }
+
+    /// Parses `raw` as TOML, after `${ENV_VAR}` interpolation. See
+    /// [`from_file`](Self::from_file).
+    pub fn from_toml_str(raw: &str) -> Result<Self, String> {
+        let interpolated = crate::agent_config::interpolate_env_vars(raw)?;
+        toml::from_str(&interpolated).map_err(|e| format!("Failed to parse agent config TOML: {}", e))
+    }
+
+    /// Parses `raw` as JSON, after `${ENV_VAR}` interpolation. See
+    /// [`from_file`](Self::from_file).
+    pub fn from_json_str(raw: &str) -> Result<Self, String> {
+        let interpolated = crate::agent_config::interpolate_env_vars(raw)?;
+        serde_json::from_str(&interpolated).map_err(|e| format!("Failed to parse agent config JSON: {}", e))
+    }
+}
+
 pub struct Agent {
     pub(crate) handle: *mut c_void,
+    /// Which provider this agent was configured with, so code outside this
+    /// module (the [`Conversation`](crate::conversation::Conversation) loop)
+    /// can pick a matching [`ToolFormat`](crate::tool_format::ToolFormat)
+    /// without re-parsing `AgentConfig`, which is consumed into JSON at
+    /// [`AgentBuilder::build`] time.
+    pub(crate) provider: ChatProvider,
+    pub(crate) confirmation_policy: ConfirmationPolicy,
+    /// Upper bound on how many rounds of function calling
+    /// [`run_until_complete`](Agent::run_until_complete) will drive before
+    /// giving up, taken from [`AgentBuilder::with_max_function_calls`].
+    pub(crate) max_function_calls: i32,
+    /// A second, fully-built agent on a secondary provider/model, set via
+    /// [`AgentBuilder::with_fallback_model`]. [`Conversation`](crate::conversation::Conversation)
+    /// retries against this one when a request to the primary model fails
+    /// with a retryable error (rate limit, 5xx, timeout) instead of
+    /// surfacing that failure to the caller.
+    pub(crate) fallback: Option<Box<Agent>>,
+    /// The [`WireEncoding`](crate::wire::WireEncoding) this agent's `handle`
+    /// was registered under via [`crate::wire::register_encoding`]. Kept
+    /// here too (not just in the registry) so [`Drop`] can unregister the
+    /// right entry without needing to re-derive it.
+    pub(crate) encoding: crate::wire::WireEncoding,
+    backend: Arc<dyn FfiBackend>,
 }
 
 impl Drop for Agent {
     fn drop(&mut self) {
         // Add a null check for safety
         if !self.handle.is_null() {
-            unsafe { ffi::destroy_agent(self.handle) };
+            crate::wire::unregister_encoding(self.handle);
+            self.backend.destroy_agent(self.handle);
             self.handle = std::ptr::null_mut();
         }
     }
@@ -124,9 +288,169 @@ impl Drop for Agent {
 unsafe impl Send for Agent {}
 unsafe impl Sync for Agent {}
 
+/// One round of an [`Agent::run_until_complete`] run: the call dispatched,
+/// its outcome, and whether it was served from the dedup cache instead of
+/// actually re-running.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StepLogEntry {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    pub result: Result<String, String>,
+    pub from_cache: bool,
+}
+
+/// The accumulated outcome of an [`Agent::run_until_complete`] run: the
+/// model's final text (if any) plus the ordered step log of every call
+/// dispatched to get there.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RunReport {
+    pub text: Option<String>,
+    pub steps: Vec<StepLogEntry>,
+}
+
+/// Canonicalizes `value` by recursively sorting object keys (arrays keep
+/// their order), so two JSON-equal-but-differently-ordered argument objects
+/// hash to the same [`cache_key`].
+fn canonicalize_json(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let sorted: std::collections::BTreeMap<String, serde_json::Value> =
+                map.iter().map(|(k, v)| (k.clone(), canonicalize_json(v))).collect();
+            serde_json::json!(sorted)
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.iter().map(canonicalize_json).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Computes the dedup cache key for a call: a hash of the function name
+/// plus its canonicalized (sorted-and-normalized) arguments, so repeat calls
+/// with differently-ordered-but-equal argument objects still hit the cache.
+fn cache_key(name: &str, arguments: &serde_json::Value) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    name.hash(&mut hasher);
+    canonicalize_json(arguments).to_string().hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+impl Agent {
+    /// Drives iterative function calling against `backend` for up to this
+    /// agent's configured `max_function_calls` rounds, instead of leaving
+    /// the whole loop to the C# side. Each round collects the model's
+    /// requested calls and dispatches them through
+    /// [`crate::plugins::dispatch_gated`] - the same approval-gated path
+    /// `rust_execute_plugin_function` uses - then feeds the results back for
+    /// the next round.
+    ///
+    /// Identical calls to non-side-effecting functions (anything not
+    /// `may_`- or `execute_`-prefixed, see [`is_side_effecting`](crate::conversation::is_side_effecting))
+    /// are deduplicated across rounds via a [`cache_key`] hash of
+    /// `(function_name, sorted-and-normalized args)`, so a model that repeats
+    /// an earlier call gets the cached result instead of paying to recompute
+    /// it. Side-effecting calls always re-run, since their outcome may
+    /// depend on side effects the cache can't see. The returned
+    /// [`RunReport`] records every dispatched call, including whether it was
+    /// served from the cache, so callers can inspect the reasoning chain.
+    pub fn run_until_complete(
+        &self,
+        message: &str,
+        backend: &dyn crate::conversation::ModelBackend,
+    ) -> Result<RunReport, String> {
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(self.run_until_complete_async(message, backend))
+        })
+    }
+
+    async fn run_until_complete_async(
+        &self,
+        message: &str,
+        backend: &dyn crate::conversation::ModelBackend,
+    ) -> Result<RunReport, String> {
+        use crate::conversation::{Message, Role};
+
+        let mut history = vec![Message {
+            role: Role::User,
+            content: message.to_string(),
+            tool_call_id: None,
+            tool_is_error: false,
+            tool_calls: Vec::new(),
+        }];
+        let mut cache: HashMap<String, String> = HashMap::new();
+        let mut report = RunReport::default();
+
+        for _ in 0..self.max_function_calls.max(0) {
+            let turn = backend.send_turn(self, &history)?;
+
+            if let Some(text) = &turn.text {
+                report.text = Some(text.clone());
+            }
+
+            // Pushed for text or requested calls, not only text - a
+            // tool-calls-only turn still needs its assistant declaration
+            // recorded before the matching `Role::Tool` results below (see
+            // the matching comment in `conversation::Conversation::run_turn_loop`).
+            if turn.text.is_some() || !turn.function_calls.is_empty() {
+                history.push(Message {
+                    role: Role::Assistant,
+                    content: turn.text.clone().unwrap_or_default(),
+                    tool_call_id: None,
+                    tool_is_error: false,
+                    tool_calls: turn.function_calls.clone(),
+                });
+            }
+
+            if turn.function_calls.is_empty() {
+                break;
+            }
+
+            for call in &turn.function_calls {
+                let cacheable = !crate::conversation::is_side_effecting(&call.name);
+                let key = cacheable.then(|| cache_key(&call.name, &call.arguments));
+
+                let (result, from_cache) = if let Some(cached) = key.as_ref().and_then(|key| cache.get(key)) {
+                    (Ok(cached.clone()), true)
+                } else {
+                    let outcome = crate::plugins::dispatch_gated(&call.name, &call.arguments.to_string()).await;
+                    let result = match outcome {
+                        crate::plugins::GatedOutcome::Ran(result) => result,
+                        crate::plugins::GatedOutcome::Pending { call_id } => {
+                            Err(format!("Call to '{}' is pending approval (call_id: {})", call.name, call_id))
+                        }
+                    };
+                    if let (Some(key), Ok(value)) = (&key, &result) {
+                        cache.insert(key.clone(), value.clone());
+                    }
+                    (result, false)
+                };
+
+                history.push(Message {
+                    role: Role::Tool,
+                    content: result.clone().unwrap_or_else(|e| e.clone()),
+                    tool_call_id: Some(call.id.clone()),
+                    tool_is_error: result.is_err(),
+                    tool_calls: Vec::new(),
+                });
+
+                report.steps.push(StepLogEntry {
+                    name: call.name.clone(),
+                    arguments: call.arguments.clone(),
+                    result,
+                    from_cache,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+}
+
 pub struct AgentBuilder {
     config: AgentConfig,
     pending_plugins: Vec<RustFunctionInfo>,
+    confirmation_policy: ConfirmationPolicy,
+    fallback: Option<ProviderConfig>,
+    backend: Arc<dyn FfiBackend>,
 }
 
 impl AgentBuilder {
@@ -137,9 +461,74 @@ impl AgentBuilder {
                 ..Default::default()
             },
             pending_plugins: Vec::new(),
+            confirmation_policy: ConfirmationPolicy::default(),
+            fallback: None,
+            backend: Arc::new(CSharpBackend),
         }
     }
 
+    /// Loads an [`AgentConfig`] from `path` (see [`AgentConfig::from_file`])
+    /// and wraps it in a builder with no plugins registered yet, so a whole
+    /// agent - name, instructions, provider, and plugin configurations - can
+    /// be declared in one file instead of builder calls with hardcoded
+    /// credentials. Plugins themselves still need
+    /// [`with_plugin`](Self::with_plugin), since those are Rust types, not
+    /// data.
+    pub fn from_config_file(path: &str) -> Result<Self, String> {
+        Ok(Self {
+            config: AgentConfig::from_file(path)?,
+            pending_plugins: Vec::new(),
+            confirmation_policy: ConfirmationPolicy::default(),
+            fallback: None,
+            backend: Arc::new(CSharpBackend),
+        })
+    }
+
+    /// Overrides the FFI backend used to create the agent, e.g. a
+    /// `MockBackend` in tests that don't have a loaded C# host.
+    pub fn with_backend(mut self, backend: Arc<dyn FfiBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Sets how side-effecting plugin calls (see
+    /// [`is_side_effecting`](crate::conversation::is_side_effecting)) are
+    /// authorized before the tool-calling loop dispatches them. Defaults to
+    /// [`ConfirmationPolicy::AutoApprove`].
+    pub fn with_confirmation_policy(mut self, policy: ConfirmationPolicy) -> Self {
+        self.confirmation_policy = policy;
+        self
+    }
+
+    /// Convenience over [`with_confirmation_policy`](Self::with_confirmation_policy)
+    /// for the common case of a closure deciding per call.
+    pub fn with_confirmation_callback(
+        mut self,
+        callback: impl FnMut(&FunctionCallRequest) -> bool + Send + 'static,
+    ) -> Self {
+        self.confirmation_policy = ConfirmationPolicy::Callback(Mutex::new(Box::new(callback)));
+        self
+    }
+
+    /// Installs the process-wide approval policy consulted by
+    /// `rust_execute_plugin_function` for gated (`may_`- or `execute_`-prefixed) calls
+    /// coming from the C# side: `Allow` runs the call immediately, `Deny`
+    /// refuses it, and `AskUser` parks it for later resolution via
+    /// `rust_approve_pending_call`. This is a separate gate from
+    /// [`with_confirmation_policy`](Self::with_confirmation_policy), which
+    /// covers calls dispatched by the Rust-native
+    /// [`Conversation`](crate::conversation::Conversation) loop instead - the
+    /// policy here lives in [`crate::plugins`] since it's consulted by a
+    /// free-standing FFI entry point with no `Agent` of its own to read it
+    /// from.
+    pub fn with_approval_policy(
+        self,
+        policy: impl Fn(&str, &serde_json::Value) -> crate::plugins::Approval + Send + Sync + 'static,
+    ) -> Self {
+        crate::plugins::set_approval_policy(policy);
+        self
+    }
+
     /// Add a plugin to this agent
     /// The plugin will be automatically registered and its functions will be available to the AI
     pub fn with_plugin<P: Plugin + 'static>(mut self, plugin: P) -> Self {
@@ -174,6 +563,28 @@ impl AgentBuilder {
         self
     }
 
+    /// Initializes the OTLP exporter `config` describes, so
+    /// [`build`](Self::build) emits a span tagged with provider/model/plugin
+    /// count and every later `rust_execute_plugin_function` call emits a
+    /// child span plus call-count/latency/error metrics. A no-op unless the
+    /// crate is built with the `otel` feature.
+    pub fn with_telemetry(self, config: crate::telemetry::OtelConfig) -> Self {
+        if let Err(error) = crate::telemetry::init(&config) {
+            eprintln!("Failed to initialize OpenTelemetry exporter: {}", error);
+        }
+        self
+    }
+
+    /// Negotiates the [`WireEncoding`](crate::wire::WireEncoding) used for
+    /// every FFI payload this agent exchanges with the C# side from
+    /// [`build`](Self::build) onward - the config and plugin metadata sent
+    /// at creation time, and every later `rust_execute_plugin_function`
+    /// call/result. Defaults to `Json`.
+    pub fn with_encoding(mut self, encoding: crate::wire::WireEncoding) -> Self {
+        self.config.encoding = encoding;
+        self
+    }
+
     pub fn with_provider(mut self, provider: ProviderConfig) -> Self {
         self.config.provider = Some(provider);
         self
@@ -219,6 +630,46 @@ impl AgentBuilder {
         self
     }
 
+    /// As [`with_openrouter`](Self::with_openrouter), but also sets
+    /// `endpoint` - e.g. `AppSettings::get_openrouter_base_url()` - so
+    /// requests go to a self-hosted or proxy-compatible endpoint instead of
+    /// OpenRouter's hosted one.
+    pub fn with_openrouter_full(mut self, model_name: &str, api_key: &str, endpoint: Option<String>) -> Self {
+        self.config.provider = Some(ProviderConfig {
+            provider: ChatProvider::OpenRouter,
+            model_name: model_name.to_string(),
+            api_key: Some(api_key.to_string()),
+            endpoint,
+        });
+        self
+    }
+
+    pub fn with_anthropic(mut self, model_name: &str, api_key: &str) -> Self {
+        self.config.provider = Some(ProviderConfig {
+            provider: ChatProvider::Anthropic,
+            model_name: model_name.to_string(),
+            api_key: Some(api_key.to_string()),
+            endpoint: None,
+        });
+        self
+    }
+
+    /// Sets a secondary provider/model [`Conversation`](crate::conversation::Conversation)
+    /// falls back to when a request to the primary model fails with a
+    /// retryable error (rate limit, 5xx, timeout). Built eagerly alongside
+    /// the primary agent in [`build`](Self::build), so the fallback is ready
+    /// to use the moment it's needed rather than paying agent-creation cost
+    /// mid-retry.
+    pub fn with_fallback_model(mut self, provider: ChatProvider, model_name: &str, api_key: Option<String>) -> Self {
+        self.fallback = Some(ProviderConfig {
+            provider,
+            model_name: model_name.to_string(),
+            api_key,
+            endpoint: None,
+        });
+        self
+    }
+
     /// Add a plugin configuration for context-aware behavior.
     /// This enables dynamic function filtering based on runtime context properties.
     pub fn with_plugin_config(mut self, plugin_name: impl Into<String>, config: PluginConfiguration) -> Self {
@@ -261,29 +712,76 @@ impl AgentBuilder {
         self.with_plugin_config(plugin_name, config)
     }
 
-    pub fn build(self) -> Result<Agent, String> {
-        let config_json = serde_json::to_string(&self.config)
-            .map_err(|e| format!("Failed to serialize config: {}", e))?;
-        
-        let c_config = CString::new(config_json)
-            .map_err(|e| format!("Failed to create CString from config: {}", e))?;
-        
-        // Serialize the plugins information for C#
-        let plugins_json = serde_json::to_string(&self.pending_plugins)
-            .map_err(|e| format!("Failed to serialize plugins: {}", e))?;
-        
-        let c_plugins = CString::new(plugins_json)
-            .map_err(|e| format!("Failed to create CString for plugins: {}", e))?;
-        
-        let agent_handle = unsafe { 
-            ffi::create_agent_with_plugins(c_config.as_ptr(), c_plugins.as_ptr()) 
-        };
-        
-        if agent_handle.is_null() {
-            Err("Failed to create agent on C# side.".to_string())
-        } else {
-            Ok(Agent { handle: agent_handle })
+    pub fn build(self) -> Result<Agent, AgentError> {
+        let provider = self.config.provider.as_ref().map_or(ChatProvider::OpenRouter, |p| p.provider);
+        let model_name = self.config.provider.as_ref().map(|p| p.model_name.clone()).unwrap_or_default();
+        let span = crate::telemetry::start_build_span(&format!("{:?}", provider), &model_name, self.pending_plugins.len());
+
+        let result = self.build_inner(provider, model_name);
+        span.finish(result.is_ok());
+        result
+    }
+
+    fn build_inner(self, provider: ChatProvider, model_name: String) -> Result<Agent, AgentError> {
+        if !self.pending_plugins.is_empty() && !provider.supports_function_calling(&model_name) {
+            return Err(AgentError::FunctionCallingUnsupported { provider, model: model_name });
         }
+
+        let encoding = self.config.encoding;
+        let config_payload = crate::wire::encode(&self.config, encoding)
+            .map_err(|e| format!("Failed to encode config: {}", e))?;
+
+        // Encode the plugins information for C#
+        let plugins_payload = crate::wire::encode(&self.pending_plugins, encoding)
+            .map_err(|e| format!("Failed to encode plugins: {}", e))?;
+
+        let agent_handle = self.backend.create_agent(&config_payload, &plugins_payload, encoding)?;
+
+        // Every later FFI payload for *this* handle (e.g.
+        // `rust_execute_plugin_function`'s args and result) is encoded the
+        // same way this agent negotiated here. Registered per handle, not
+        // process-wide, since a fallback or `/model`-swapped agent built
+        // later may negotiate a different encoding without disturbing this
+        // one's in-flight calls.
+        crate::wire::register_encoding(agent_handle, encoding);
+
+        let fallback = match self.fallback {
+            Some(fallback_provider) => {
+                let fallback_chat_provider = fallback_provider.provider;
+                let mut fallback_config = self.config.clone();
+                fallback_config.provider = Some(fallback_provider);
+
+                let fallback_config_payload = crate::wire::encode(&fallback_config, encoding)
+                    .map_err(|e| format!("Failed to encode fallback config: {}", e))?;
+                let fallback_handle = self.backend.create_agent(&fallback_config_payload, &plugins_payload, encoding)?;
+                crate::wire::register_encoding(fallback_handle, encoding);
+
+                Some(Box::new(Agent {
+                    handle: fallback_handle,
+                    provider: fallback_chat_provider,
+                    // The gating decision for a side-effecting call belongs
+                    // to the conversation, not to whichever model happened to
+                    // answer it - `Conversation` always consults the primary
+                    // agent's `confirmation_policy`, never this one.
+                    confirmation_policy: ConfirmationPolicy::AutoApprove,
+                    max_function_calls: fallback_config.max_function_calls,
+                    fallback: None,
+                    encoding,
+                    backend: Arc::clone(&self.backend),
+                }))
+            }
+            None => None,
+        };
+
+        Ok(Agent {
+            handle: agent_handle,
+            provider,
+            confirmation_policy: self.confirmation_policy,
+            max_function_calls: self.config.max_function_calls,
+            fallback,
+            encoding,
+            backend: self.backend,
+        })
     }
 
     #[cfg(test)]
@@ -291,3 +789,49 @@ impl AgentBuilder {
         serde_json::to_string(&self.config).unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopPlugin;
+
+    impl Plugin for NoopPlugin {
+        fn register_functions(&self) {}
+
+        fn get_plugin_info(&self) -> Vec<RustFunctionInfo> {
+            vec![RustFunctionInfo {
+                name: "noop".to_string(),
+                description: "Does nothing".to_string(),
+                wrapper_function_name: "noop_wrapper".to_string(),
+                schema: "{}".to_string(),
+                requires_permission: false,
+                required_permissions: vec![],
+            }]
+        }
+    }
+
+    #[test]
+    fn test_build_returns_typed_error_for_unsupported_function_calling() {
+        let backend: Arc<dyn FfiBackend> = Arc::new(crate::ffi_backend::MockBackend::new());
+        let result = AgentBuilder::new("Test Agent")
+            .with_backend(backend)
+            .with_plugin(NoopPlugin)
+            .with_provider(ProviderConfig {
+                provider: ChatProvider::AppleIntelligence,
+                model_name: "on-device".to_string(),
+                api_key: None,
+                endpoint: None,
+            })
+            .build();
+
+        let error = result.err().expect("build should reject a plugin-bearing agent on a non-tool-calling provider");
+        match error {
+            AgentError::FunctionCallingUnsupported { provider, model } => {
+                assert_eq!(provider, ChatProvider::AppleIntelligence);
+                assert_eq!(model, "on-device");
+            }
+            other => panic!("expected AgentError::FunctionCallingUnsupported, got {:?}", other),
+        }
+    }
+}
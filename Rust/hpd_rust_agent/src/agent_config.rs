@@ -0,0 +1,180 @@
+//! Declarative config-file loading for [`AgentBuilder`], with `${ENV_VAR}`
+//! interpolation for secrets.
+//!
+//! Hardcoding an API key directly in a [`ProviderConfig`] literal leaks the
+//! secret into source control and can't vary per environment.
+//! [`AgentFileConfig::load`] replaces that: it
+//! reads a TOML file describing the provider block, model, endpoint, and the
+//! same [`PluginConfiguration`] entries that are otherwise constructed
+//! programmatically (as in `examples/plugin_configuration_example.rs`), with
+//! any `${ENV_VAR}` placeholder in the file resolved against the process
+//! environment before parsing - so `api_key = "${OPENROUTER_API_KEY}"` reads
+//! the real key from the environment instead of storing it in the file.
+//! Unknown fields are rejected outright (`deny_unknown_fields`) so a typo in
+//! the config doesn't silently fall back to a default. [`AgentFileConfig::into_builder`]
+//! merges the file's settings onto a caller-supplied [`AgentBuilder`]:
+//! `with_*` calls chained after it override the file's settings, while the
+//! provider (and the instructions, when the file has them) it sets replace
+//! any applied to the builder before.
+
+use std::collections::HashMap;
+
+use crate::agent::{AgentBuilder, ChatProvider, ProviderConfig};
+use crate::plugin_context::PluginConfiguration;
+
+/// A declarative, file-loaded description of an agent's provider and plugin
+/// configuration.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentFileConfig {
+    pub name: Option<String>,
+    pub instructions: Option<String>,
+    pub provider: ChatProvider,
+    pub model: String,
+    pub api_key: Option<String>,
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub plugins: HashMap<String, PluginConfiguration>,
+}
+
+impl AgentFileConfig {
+    /// Reads `path` as TOML, interpolating any `${ENV_VAR}` placeholder
+    /// against the process environment before parsing.
+    ///
+    /// Returns an error if the file can't be read, references an unset
+    /// environment variable, fails to parse, or contains a field this
+    /// config doesn't recognize.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read agent config '{}': {}", path, e))?;
+        let interpolated = interpolate_env_vars(&raw)?;
+
+        toml::from_str(&interpolated)
+            .map_err(|e| format!("Failed to parse agent config '{}': {}", path, e))
+    }
+
+    /// Merges this config onto `builder`: sets the provider and instructions
+    /// (when present) and registers each plugin's [`PluginConfiguration`].
+    /// Called before any other builder methods, so callers can still
+    /// override individual settings by chaining further `with_*` calls
+    /// after this one.
+    pub fn into_builder(self, mut builder: AgentBuilder) -> AgentBuilder {
+        if let Some(instructions) = self.instructions {
+            builder = builder.with_instructions(&instructions);
+        }
+
+        builder = builder.with_provider(ProviderConfig {
+            provider: self.provider,
+            model_name: self.model,
+            api_key: self.api_key,
+            endpoint: self.endpoint,
+        });
+
+        for (name, config) in self.plugins {
+            builder = builder.with_plugin_config(name, config);
+        }
+
+        builder
+    }
+}
+
+/// Replaces every `${VAR_NAME}` placeholder in `input` with the value of the
+/// `VAR_NAME` environment variable. Errors if any referenced variable isn't
+/// set, so a missing secret fails loudly at config-load time rather than
+/// silently embedding the literal placeholder text.
+///
+/// `pub(crate)` rather than private: [`crate::agent::AgentConfig::from_file`]
+/// reuses this same interpolation pass instead of duplicating it.
+pub(crate) fn interpolate_env_vars(input: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            result.push_str(rest);
+            return Ok(result);
+        };
+        let end = start + end;
+
+        result.push_str(&rest[..start]);
+        let var_name = &rest[start + 2..end];
+        let value = std::env::var(var_name)
+            .map_err(|_| format!("Agent config references unset environment variable '{}'", var_name))?;
+        result.push_str(&value);
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate_env_vars_substitutes_set_variable() {
+        std::env::set_var("HPD_AGENT_CONFIG_TEST_KEY", "secret-value");
+        let result = interpolate_env_vars(r#"api_key = "${HPD_AGENT_CONFIG_TEST_KEY}""#).unwrap();
+        assert_eq!(result, r#"api_key = "secret-value""#);
+        std::env::remove_var("HPD_AGENT_CONFIG_TEST_KEY");
+    }
+
+    #[test]
+    fn test_interpolate_env_vars_errors_on_unset_variable() {
+        std::env::remove_var("HPD_AGENT_CONFIG_TEST_MISSING");
+        let result = interpolate_env_vars("${HPD_AGENT_CONFIG_TEST_MISSING}");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_load_parses_provider_block_and_plugin_configs() {
+        std::env::set_var("HPD_AGENT_CONFIG_TEST_OR_KEY", "sk-test-123");
+
+        let toml = r#"
+            name = "Function Test Agent"
+            instructions = "You are a test agent."
+            provider = "OpenRouter"
+            model = "google/gemini-2.5-pro"
+            api_key = "${HPD_AGENT_CONFIG_TEST_OR_KEY}"
+            endpoint = "https://openrouter.ai/api/v1"
+
+            [plugins.MathPlugin]
+            pluginName = "MathPlugin"
+            contextType = "MathPluginMetadataContext"
+
+            [plugins.MathPlugin.properties]
+            precision = 2
+        "#;
+
+        let path = std::env::temp_dir().join("hpd_agent_config_test.toml");
+        std::fs::write(&path, toml).unwrap();
+
+        let config = AgentFileConfig::load(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(config.api_key.as_deref(), Some("sk-test-123"));
+        assert_eq!(config.provider, ChatProvider::OpenRouter);
+        assert!(config.plugins.contains_key("MathPlugin"));
+
+        std::fs::remove_file(&path).ok();
+        std::env::remove_var("HPD_AGENT_CONFIG_TEST_OR_KEY");
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_fields() {
+        let toml = r#"
+            provider = "OpenAI"
+            model = "gpt-4"
+            totally_unknown_field = true
+        "#;
+
+        let path = std::env::temp_dir().join("hpd_agent_config_test_unknown_field.toml");
+        std::fs::write(&path, toml).unwrap();
+
+        let result = AgentFileConfig::load(path.to_str().unwrap());
+        assert!(result.is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,408 @@
+//! Validates and coerces plugin-function call arguments against the JSON
+//! Schema [`crate::plugins::get_all_schemas`] and [`rhai_plugins`](crate::rhai_plugins)
+//! generate, before a call ever reaches an
+//! [`AsyncFunctionExecutor`](crate::plugins::register_async_executor).
+//!
+//! [`schema_constraints`](crate::schema_constraints) only describes the
+//! bounds a schema carries; this module is the "actually validate" half it
+//! deliberately left undone. [`validate_args`] checks every declared field
+//! against its schema, coercing values the model got close but not exact on
+//! (a numeric string for a `number` parameter, an integer-valued float for
+//! an `integer` one, `"true"` for a `boolean`) and enforcing
+//! `minimum`/`maximum`/`maxLength`/`pattern`/`enum`, producing a
+//! [`ValidationReport`] with a per-field diagnostic rather than bailing out
+//! on the first problem — so a caller can show the model exactly which
+//! field was wrong and what was expected instead of a single opaque parse
+//! error. [`validate_and_coerce_args`] is the `Result`-based convenience
+//! [`crate::plugins::execute_function_async`] uses before dispatch, and
+//! [`rust_validate_plugin_args`] exposes the full report across the FFI
+//! boundary so a caller can pre-validate a tool call before re-invoking.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+use serde_json::Value as JsonValue;
+
+/// One field's outcome from validating a call's arguments against its
+/// schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldStatus {
+    /// Matched its declared type (and constraints) as given.
+    Ok,
+    /// Didn't match its declared type, but was safely converted (numeric
+    /// string to number, integer-valued float to int, etc) or filled in
+    /// from the schema's `default`.
+    Coerced,
+    /// Didn't match its declared type or constraints, and couldn't be
+    /// converted.
+    Rejected,
+    /// A required field was absent from the call and had no `default`.
+    Missing,
+}
+
+/// Per-field detail backing a [`ValidationReport`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FieldDiagnostic {
+    pub field: String,
+    pub expected_type: String,
+    pub found_type: String,
+    pub status: FieldStatus,
+    /// Extra detail for a [`FieldStatus::Rejected`] caused by a constraint
+    /// violation (out of range, wrong pattern, ...) rather than a type
+    /// mismatch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The outcome of validating (and where possible, coercing) a call's
+/// arguments against its schema: whether the call can proceed, the coerced
+/// arguments to actually dispatch with, and a diagnostic for every field
+/// checked, including ones that matched as given (status `Ok`).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub coerced_args: JsonValue,
+    pub fields: Vec<FieldDiagnostic>,
+}
+
+impl ValidationReport {
+    /// Renders every rejected/missing field as a single human-readable
+    /// message, for callers (like [`crate::plugins::execute_function_async`])
+    /// that just want an `Err(String)` rather than the full report.
+    pub fn to_error_message(&self) -> String {
+        self.fields.iter()
+            .filter(|f| matches!(f.status, FieldStatus::Rejected | FieldStatus::Missing))
+            .map(|f| match (&f.status, &f.message) {
+                (FieldStatus::Missing, _) => format!("'{}' is required but missing", f.field),
+                (_, Some(message)) => format!("'{}' {}", f.field, message),
+                (_, None) => format!("'{}' expected {} but found {}", f.field, f.expected_type, f.found_type),
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Validates `args` against `schema` (the `{"type":"function","function":{"parameters":{...}}}`
+/// shape [`crate::plugins::get_all_schemas`] returns), producing a
+/// diagnostic for every declared field that is present in `args` or
+/// required, instead of stopping at the first problem. A declared optional
+/// field absent from `args` gets no diagnostic.
+///
+/// A schema with no recognizable `parameters.properties` is treated as
+/// "nothing to validate against" rather than a failure — this is the
+/// fallback for callers that don't have a schema, not a way to skip
+/// validation.
+pub fn validate_args(schema: &JsonValue, args: &JsonValue) -> ValidationReport {
+    let Some(properties) = schema.pointer("/function/parameters/properties").and_then(JsonValue::as_object) else {
+        return ValidationReport { valid: true, coerced_args: args.clone(), fields: Vec::new() };
+    };
+
+    let Some(mut args_obj) = args.as_object().cloned() else {
+        return ValidationReport {
+            valid: false,
+            coerced_args: args.clone(),
+            fields: vec![FieldDiagnostic {
+                field: "<arguments>".to_string(),
+                expected_type: "object".to_string(),
+                found_type: json_type_name(args).to_string(),
+                status: FieldStatus::Rejected,
+                message: None,
+            }],
+        };
+    };
+
+    let required: Vec<&str> = schema.pointer("/function/parameters/required")
+        .and_then(JsonValue::as_array)
+        .map(|values| values.iter().filter_map(JsonValue::as_str).collect())
+        .unwrap_or_default();
+
+    let mut fields = Vec::new();
+    let mut valid = true;
+
+    for name in &required {
+        if args_obj.contains_key(*name) {
+            continue;
+        }
+
+        let property = properties.get(*name);
+        let expected_type = property.map(expected_type_name).unwrap_or_else(|| "any".to_string());
+
+        match property.and_then(|p| p.get("default")).cloned() {
+            Some(default) => {
+                args_obj.insert((*name).to_string(), default);
+                fields.push(FieldDiagnostic {
+                    field: (*name).to_string(),
+                    expected_type,
+                    found_type: "missing".to_string(),
+                    status: FieldStatus::Coerced,
+                    message: Some("filled in from schema default".to_string()),
+                });
+            }
+            None => {
+                valid = false;
+                fields.push(FieldDiagnostic {
+                    field: (*name).to_string(),
+                    expected_type,
+                    found_type: "missing".to_string(),
+                    status: FieldStatus::Missing,
+                    message: None,
+                });
+            }
+        }
+    }
+
+    for (name, property) in properties {
+        let Some(value) = args_obj.get(name).cloned() else { continue };
+        let expected_type = expected_type_name(property);
+        let found_type = json_type_name(&value).to_string();
+
+        let Some(coerced) = coerce_value(&value, property) else {
+            valid = false;
+            fields.push(FieldDiagnostic { field: name.clone(), expected_type, found_type, status: FieldStatus::Rejected, message: None });
+            continue;
+        };
+
+        if let Err(message) = check_constraints(&coerced, property) {
+            valid = false;
+            fields.push(FieldDiagnostic { field: name.clone(), expected_type, found_type, status: FieldStatus::Rejected, message: Some(message) });
+            continue;
+        }
+
+        let status = if coerced == value { FieldStatus::Ok } else { FieldStatus::Coerced };
+        fields.push(FieldDiagnostic { field: name.clone(), expected_type, found_type, status, message: None });
+        args_obj.insert(name.clone(), coerced);
+    }
+
+    ValidationReport { valid, coerced_args: JsonValue::Object(args_obj), fields }
+}
+
+/// Validates and coerces `args` against `schema`, returning the coerced
+/// arguments or an `Err` summarizing every rejected/missing field.
+pub fn validate_and_coerce_args(schema: &JsonValue, args: &JsonValue) -> Result<JsonValue, String> {
+    let report = validate_args(schema, args);
+    if report.valid {
+        Ok(report.coerced_args)
+    } else {
+        Err(report.to_error_message())
+    }
+}
+
+/// Coerces `value` to match `property`'s declared `type`, if it isn't
+/// already that type but can be converted unambiguously. Returns `None` if
+/// the value can't be made to fit.
+fn coerce_value(value: &JsonValue, property: &JsonValue) -> Option<JsonValue> {
+    let Some(expected_type) = property.get("type").and_then(JsonValue::as_str) else {
+        return Some(value.clone());
+    };
+
+    match (expected_type, value) {
+        ("integer", JsonValue::Number(n)) if n.is_i64() || n.is_u64() => Some(value.clone()),
+        ("integer", JsonValue::Number(n)) => n.as_f64()
+            .filter(|f| f.fract() == 0.0)
+            .map(|f| serde_json::json!(f as i64)),
+        ("integer", JsonValue::String(s)) => s.trim().parse::<i64>().ok().map(|n| serde_json::json!(n)),
+        ("number", JsonValue::Number(_)) => Some(value.clone()),
+        ("number", JsonValue::String(s)) => s.trim().parse::<f64>().ok().map(|f| serde_json::json!(f)),
+        ("string", JsonValue::String(_)) => Some(value.clone()),
+        ("string", JsonValue::Number(n)) => Some(serde_json::json!(n.to_string())),
+        ("boolean", JsonValue::Bool(_)) => Some(value.clone()),
+        ("boolean", JsonValue::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Some(serde_json::json!(true)),
+            "false" => Some(serde_json::json!(false)),
+            _ => None,
+        },
+        ("object", JsonValue::Object(_)) => Some(value.clone()),
+        ("array", JsonValue::Array(_)) => Some(value.clone()),
+        ("null", JsonValue::Null) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Checks `value` against whichever of `minimum`/`maximum`/`maxLength`/
+/// `pattern`/`enum` are present on `property` ([`crate::schema_constraints::merge_constraints`]
+/// is what puts them there).
+fn check_constraints(value: &JsonValue, property: &JsonValue) -> Result<(), String> {
+    if let Some(min) = property.get("minimum").and_then(JsonValue::as_f64) {
+        if value.as_f64().is_some_and(|n| n < min) {
+            return Err(format!("is below its minimum of {}", min));
+        }
+    }
+
+    if let Some(max) = property.get("maximum").and_then(JsonValue::as_f64) {
+        if value.as_f64().is_some_and(|n| n > max) {
+            return Err(format!("is above its maximum of {}", max));
+        }
+    }
+
+    if let Some(max_len) = property.get("maxLength").and_then(JsonValue::as_u64) {
+        if let Some(s) = value.as_str() {
+            if s.chars().count() as u64 > max_len {
+                return Err(format!("exceeds its maximum length of {}", max_len));
+            }
+        }
+    }
+
+    if let Some(pattern) = property.get("pattern").and_then(JsonValue::as_str) {
+        if let Some(s) = value.as_str() {
+            let regex = regex::Regex::new(pattern)
+                .map_err(|e| format!("has an invalid schema pattern: {}", e))?;
+            if !regex.is_match(s) {
+                return Err("doesn't match its required pattern".to_string());
+            }
+        }
+    }
+
+    if let Some(allowed) = property.get("enum").and_then(JsonValue::as_array) {
+        if !allowed.contains(value) {
+            return Err("isn't one of its allowed values".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+fn expected_type_name(property: &JsonValue) -> String {
+    property.get("type").and_then(JsonValue::as_str).unwrap_or("any").to_string()
+}
+
+fn json_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+/// FFI entry point that validates (without executing) a prospective call's
+/// arguments against `name`'s registered schema (see
+/// [`crate::plugins::get_schema_for`]).
+///
+/// Returns a newly allocated null-terminated JSON [`ValidationReport`], so a
+/// caller can show the model a precise repair hint before re-invoking
+/// rather than relying on the opaque failure a bad call would hit on actual
+/// dispatch. A non-null return value must be released with
+/// [`rust_validate_plugin_args_free_string`].
+///
+/// # Safety
+/// `name_ptr` and `args_ptr` must be valid, null-terminated C strings for
+/// the duration of this call.
+#[no_mangle]
+pub unsafe extern "C" fn rust_validate_plugin_args(name_ptr: *const c_char, args_ptr: *const c_char) -> *mut c_char {
+    if name_ptr.is_null() || args_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let name = CStr::from_ptr(name_ptr).to_string_lossy().into_owned();
+    let args_json = CStr::from_ptr(args_ptr).to_string_lossy().into_owned();
+
+    let report = match serde_json::from_str::<JsonValue>(&args_json) {
+        Err(e) => ValidationReport {
+            valid: false,
+            coerced_args: JsonValue::Null,
+            fields: vec![FieldDiagnostic {
+                field: "<arguments>".to_string(),
+                expected_type: "valid JSON".to_string(),
+                found_type: "unparseable".to_string(),
+                status: FieldStatus::Rejected,
+                message: Some(format!("failed to parse: {}", e)),
+            }],
+        },
+        Ok(args) => match crate::plugins::get_schema_for(&name) {
+            Some(schema) => validate_args(&schema, &args),
+            None => ValidationReport { valid: true, coerced_args: args, fields: Vec::new() },
+        },
+    };
+
+    CString::new(serde_json::to_string(&report).unwrap_or_default())
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// Releases a string returned by [`rust_validate_plugin_args`].
+///
+/// # Safety
+/// `ptr` must have been returned by [`rust_validate_plugin_args`] and not
+/// already freed.
+#[no_mangle]
+pub unsafe extern "C" fn rust_validate_plugin_args_free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> JsonValue {
+        serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": "factorial",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "n": { "type": "integer", "minimum": 0.0, "maximum": 20.0 },
+                        "label": { "type": "string", "maxLength": 5 },
+                    },
+                    "required": ["n"],
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_coerces_numeric_string_to_integer() {
+        let args = serde_json::json!({ "n": "5" });
+        let result = validate_and_coerce_args(&schema(), &args).unwrap();
+        assert_eq!(result["n"], serde_json::json!(5));
+    }
+
+    #[test]
+    fn test_rejects_missing_required_argument() {
+        let args = serde_json::json!({ "label": "hi" });
+        let error = validate_and_coerce_args(&schema(), &args).unwrap_err();
+        assert!(error.contains("n"));
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_value() {
+        let args = serde_json::json!({ "n": 100 });
+        let error = validate_and_coerce_args(&schema(), &args).unwrap_err();
+        assert!(error.contains("maximum"));
+    }
+
+    #[test]
+    fn test_rejects_string_exceeding_max_length() {
+        let args = serde_json::json!({ "n": 1, "label": "way too long" });
+        let error = validate_and_coerce_args(&schema(), &args).unwrap_err();
+        assert!(error.contains("length"));
+    }
+
+    #[test]
+    fn test_passes_through_unvalidated_when_no_schema_properties() {
+        let schema = serde_json::json!({ "function": { "parameters": {} } });
+        let args = serde_json::json!({ "anything": "goes" });
+        let result = validate_and_coerce_args(&schema, &args).unwrap();
+        assert_eq!(result, args);
+    }
+
+    #[test]
+    fn test_validate_args_reports_status_per_field() {
+        let args = serde_json::json!({ "n": "5", "label": "ok" });
+        let report = validate_args(&schema(), &args);
+
+        assert!(report.valid);
+        let n_field = report.fields.iter().find(|f| f.field == "n").unwrap();
+        assert_eq!(n_field.status, FieldStatus::Coerced);
+        assert_eq!(n_field.expected_type, "integer");
+        assert_eq!(n_field.found_type, "string");
+
+        let label_field = report.fields.iter().find(|f| f.field == "label").unwrap();
+        assert_eq!(label_field.status, FieldStatus::Ok);
+    }
+}
@@ -0,0 +1,483 @@
+//! Native condition-expression engine for `ai_function` availability gates.
+//!
+//! Every conditional function gate used to call back into C# via
+//! `ffi::evaluate_precompiled_condition`, which is slow and makes the gate
+//! logic invisible to Rust. This module implements a small expression
+//! language so conditions can be authored as strings like
+//! `provider == "Tavily" && maxResults > 5` on the `ai_function` attribute
+//! and evaluated directly against [`PluginContext::properties`](crate::plugin_context::PluginContext).
+//!
+//! Supported syntax: `== != < <= > >= && || !` plus `in [..]` membership.
+//! Identifiers resolve against the context's properties; a missing property
+//! evaluates to `false` rather than erroring, so a condition referencing an
+//! optional property degrades gracefully.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde_json::Value as JsonValue;
+
+use crate::plugin_context::PluginContext;
+
+/// A literal value in a condition expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Null,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnOp {
+    Not,
+}
+
+/// The parsed form of a condition expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Ident(String),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
+    In(Box<Expr>, Vec<Value>),
+}
+
+/// AST compiled from a condition string, cached keyed by `(plugin_type,
+/// function_name)` so repeated availability checks skip reparsing.
+static COMPILED_CONDITIONS: Lazy<Mutex<HashMap<(String, String), Expr>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The condition source string attached to each `(plugin_type,
+/// function_name)` by the `ai_function` macro, e.g. `provider == "Tavily"`.
+static CONDITION_SOURCES: Lazy<Mutex<HashMap<(String, String), String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Registers the condition string an `ai_function` attribute declared for a
+/// plugin function, so it can later be looked up and compiled on demand.
+pub fn register_condition_source(plugin_type: &str, function_name: &str, condition: &str) {
+    if let Ok(mut sources) = CONDITION_SOURCES.lock() {
+        sources.insert((plugin_type.to_string(), function_name.to_string()), condition.to_string());
+    }
+}
+
+/// Looks up the condition string registered for `(plugin_type,
+/// function_name)`, if any.
+pub fn condition_source_for(plugin_type: &str, function_name: &str) -> Option<String> {
+    CONDITION_SOURCES.lock().ok()?
+        .get(&(plugin_type.to_string(), function_name.to_string()))
+        .cloned()
+}
+
+/// Compiles `condition` for `(plugin_type, function_name)`, reusing a
+/// previously compiled AST for the same key if present.
+///
+/// Returns `None` if the condition cannot be parsed by the native engine, so
+/// callers can fall back to the FFI evaluator.
+pub fn compile_condition(plugin_type: &str, function_name: &str, condition: &str) -> Option<Expr> {
+    let key = (plugin_type.to_string(), function_name.to_string());
+
+    if let Some(cached) = COMPILED_CONDITIONS.lock().ok().and_then(|cache| cache.get(&key).cloned()) {
+        return Some(cached);
+    }
+
+    let ast = Parser::new(condition).and_then(Parser::parse).ok()?;
+
+    if let Ok(mut cache) = COMPILED_CONDITIONS.lock() {
+        cache.insert(key, ast.clone());
+    }
+
+    Some(ast)
+}
+
+/// Evaluates a compiled condition against a plugin context's properties.
+pub fn evaluate(ast: &Expr, context: &PluginContext) -> bool {
+    match eval_value(ast, context) {
+        Value::Bool(b) => b,
+        Value::Null => false,
+        Value::Number(n) => n != 0.0,
+        Value::String(s) => !s.is_empty(),
+    }
+}
+
+fn eval_value(expr: &Expr, context: &PluginContext) -> Value {
+    match expr {
+        Expr::Literal(v) => v.clone(),
+        Expr::Ident(name) => context.properties.get(name)
+            .map(json_to_value)
+            .unwrap_or(Value::Null),
+        Expr::Unary(UnOp::Not, inner) => Value::Bool(!truthy(&eval_value(inner, context))),
+        Expr::Binary(BinOp::And, lhs, rhs) => Value::Bool(truthy(&eval_value(lhs, context)) && truthy(&eval_value(rhs, context))),
+        Expr::Binary(BinOp::Or, lhs, rhs) => Value::Bool(truthy(&eval_value(lhs, context)) || truthy(&eval_value(rhs, context))),
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = eval_value(lhs, context);
+            let rhs = eval_value(rhs, context);
+            Value::Bool(compare(*op, &lhs, &rhs))
+        }
+        Expr::In(inner, options) => {
+            let value = eval_value(inner, context);
+            Value::Bool(options.contains(&value))
+        }
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Null => false,
+        Value::Number(n) => *n != 0.0,
+        Value::String(s) => !s.is_empty(),
+    }
+}
+
+fn compare(op: BinOp, lhs: &Value, rhs: &Value) -> bool {
+    match op {
+        BinOp::Eq => values_eq(lhs, rhs),
+        BinOp::Ne => !values_eq(lhs, rhs),
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            let ordering = match (lhs, rhs) {
+                (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            match (op, ordering) {
+                (BinOp::Lt, Some(o)) => o.is_lt(),
+                (BinOp::Le, Some(o)) => o.is_le(),
+                (BinOp::Gt, Some(o)) => o.is_gt(),
+                (BinOp::Ge, Some(o)) => o.is_ge(),
+                _ => false,
+            }
+        }
+        BinOp::And | BinOp::Or => unreachable!("handled in eval_value"),
+    }
+}
+
+fn values_eq(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        (Value::Number(a), Value::Number(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::Null, Value::Null) => true,
+        _ => false,
+    }
+}
+
+fn json_to_value(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(b) => Value::Bool(*b),
+        JsonValue::Number(n) => Value::Number(n.as_f64().unwrap_or(0.0)),
+        JsonValue::String(s) => Value::String(s.clone()),
+        other => Value::String(other.to_string()),
+    }
+}
+
+// --- Recursive-descent parser -------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    True,
+    False,
+    And,
+    Or,
+    Not,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' { tokens.push(Token::LParen); i += 1; }
+        else if c == ')' { tokens.push(Token::RParen); i += 1; }
+        else if c == '[' { tokens.push(Token::LBracket); i += 1; }
+        else if c == ']' { tokens.push(Token::RBracket); i += 1; }
+        else if c == ',' { tokens.push(Token::Comma); i += 1; }
+        else if c == '"' || c == '\'' {
+            let quote = c;
+            i += 1;
+            let start = i;
+            while i < chars.len() && chars[i] != quote {
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err("Unterminated string literal".to_string());
+            }
+            tokens.push(Token::Str(chars[start..i].iter().collect()));
+            i += 1;
+        } else if c == '&' && chars.get(i + 1) == Some(&'&') {
+            tokens.push(Token::And);
+            i += 2;
+        } else if c == '|' && chars.get(i + 1) == Some(&'|') {
+            tokens.push(Token::Or);
+            i += 2;
+        } else if c == '=' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Eq);
+            i += 2;
+        } else if c == '!' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Ne);
+            i += 2;
+        } else if c == '<' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Le);
+            i += 2;
+        } else if c == '>' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Ge);
+            i += 2;
+        } else if c == '<' { tokens.push(Token::Lt); i += 1; }
+        else if c == '>' { tokens.push(Token::Gt); i += 1; }
+        else if c == '!' { tokens.push(Token::Not); i += 1; }
+        else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text.parse::<f64>().map_err(|_| format!("Invalid number literal: {}", text))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(match word.as_str() {
+                "true" => Token::True,
+                "false" => Token::False,
+                "in" => Token::In,
+                _ => Token::Ident(word),
+            });
+        } else {
+            return Err(format!("Unexpected character '{}' in condition", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Empty condition".to_string());
+        }
+        Ok(Self { tokens, pos: 0 })
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.parse_or()?;
+        if self.pos != self.tokens.len() {
+            return Err(format!("Unexpected trailing tokens at position {}", self.pos));
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            let rhs = self.parse_and()?;
+            lhs = Expr::Binary(BinOp::Or, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.advance();
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Binary(BinOp::And, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.advance();
+            return Ok(Expr::Unary(UnOp::Not, Box::new(self.parse_unary()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.parse_in()?;
+        let op = match self.peek() {
+            Some(Token::Eq) => Some(BinOp::Eq),
+            Some(Token::Ne) => Some(BinOp::Ne),
+            Some(Token::Lt) => Some(BinOp::Lt),
+            Some(Token::Le) => Some(BinOp::Le),
+            Some(Token::Gt) => Some(BinOp::Gt),
+            Some(Token::Ge) => Some(BinOp::Ge),
+            _ => None,
+        };
+
+        if let Some(op) = op {
+            self.advance();
+            let rhs = self.parse_in()?;
+            return Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)));
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_in(&mut self) -> Result<Expr, String> {
+        let lhs = self.parse_primary()?;
+        if self.peek() == Some(&Token::In) {
+            self.advance();
+            if self.advance() != Some(Token::LBracket) {
+                return Err("Expected '[' after 'in'".to_string());
+            }
+            let mut options = Vec::new();
+            if self.peek() != Some(&Token::RBracket) {
+                loop {
+                    options.push(self.parse_literal_value()?);
+                    if self.peek() == Some(&Token::Comma) {
+                        self.advance();
+                        continue;
+                    }
+                    break;
+                }
+            }
+            if self.advance() != Some(Token::RBracket) {
+                return Err("Expected ']' to close 'in' list".to_string());
+            }
+            return Ok(Expr::In(Box::new(lhs), options));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_literal_value(&mut self) -> Result<Value, String> {
+        match self.advance() {
+            Some(Token::Number(n)) => Ok(Value::Number(n)),
+            Some(Token::Str(s)) => Ok(Value::String(s)),
+            Some(Token::True) => Ok(Value::Bool(true)),
+            Some(Token::False) => Ok(Value::Bool(false)),
+            other => Err(format!("Expected a literal value in 'in [..]' list, got {:?}", other)),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.advance() {
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                if self.advance() != Some(Token::RParen) {
+                    return Err("Expected closing ')'".to_string());
+                }
+                Ok(inner)
+            }
+            Some(Token::Number(n)) => Ok(Expr::Literal(Value::Number(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::True) => Ok(Expr::Literal(Value::Bool(true))),
+            Some(Token::False) => Ok(Expr::Literal(Value::Bool(false))),
+            Some(Token::Ident(name)) => Ok(Expr::Ident(name)),
+            other => Err(format!("Unexpected token in condition: {:?}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_context::PluginContext;
+
+    fn context_with(pairs: &[(&str, JsonValue)]) -> PluginContext {
+        let mut context = PluginContext::new();
+        for (key, value) in pairs {
+            context.properties.insert(key.to_string(), value.clone());
+        }
+        context
+    }
+
+    #[test]
+    fn test_equality_and_comparison() {
+        let context = context_with(&[
+            ("provider", JsonValue::String("Tavily".to_string())),
+            ("maxResults", JsonValue::from(10)),
+        ]);
+
+        let ast = Parser::new(r#"provider == "Tavily" && maxResults > 5"#).unwrap().parse().unwrap();
+        assert!(evaluate(&ast, &context));
+
+        let ast = Parser::new(r#"provider == "Bing""#).unwrap().parse().unwrap();
+        assert!(!evaluate(&ast, &context));
+    }
+
+    #[test]
+    fn test_missing_property_is_false() {
+        let context = PluginContext::new();
+        let ast = Parser::new("hasPermission == true").unwrap().parse().unwrap();
+        assert!(!evaluate(&ast, &context));
+    }
+
+    #[test]
+    fn test_in_membership() {
+        let context = context_with(&[("provider", JsonValue::String("Tavily".to_string()))]);
+        let ast = Parser::new(r#"provider in ["Tavily", "Bing"]"#).unwrap().parse().unwrap();
+        assert!(evaluate(&ast, &context));
+
+        let ast = Parser::new(r#"provider in ["Bing"]"#).unwrap().parse().unwrap();
+        assert!(!evaluate(&ast, &context));
+    }
+
+    #[test]
+    fn test_negation_and_grouping() {
+        let context = context_with(&[("enableImageSearch", JsonValue::Bool(false))]);
+        let ast = Parser::new("!(enableImageSearch)").unwrap().parse().unwrap();
+        assert!(evaluate(&ast, &context));
+    }
+
+    #[test]
+    fn test_condition_cache_reuses_compiled_ast() {
+        let first = compile_condition("TestPlugin", "search", "a == b").unwrap();
+        let second = compile_condition("TestPlugin", "search", "a == b").unwrap();
+        assert_eq!(first, second);
+    }
+}
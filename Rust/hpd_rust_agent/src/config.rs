@@ -1,6 +1,14 @@
 use serde::Deserialize;
 use std::fs;
 
+/// OpenRouter's standard hosted endpoint, used when `appsettings.json`
+/// doesn't set `OpenRouter.BaseUrl`.
+pub const DEFAULT_OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
+/// OpenAI's standard hosted endpoint, used when `appsettings.json` doesn't
+/// set `OpenAI.BaseUrl`.
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
 #[derive(Deserialize, Debug)]
 pub struct AppSettings {
     #[serde(rename = "OpenRouter")]
@@ -15,12 +23,20 @@ pub struct AppSettings {
 pub struct OpenRouterConfig {
     #[serde(rename = "ApiKey")]
     pub api_key: String,
+    /// Overrides the hosted OpenRouter endpoint, e.g. to point at a
+    /// self-hosted proxy that speaks the same OpenAI-compatible API.
+    #[serde(rename = "BaseUrl")]
+    pub base_url: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct OpenAIConfig {
     #[serde(rename = "ApiKey")]
     pub api_key: String,
+    /// Overrides the hosted OpenAI endpoint, e.g. to point at a local LLM
+    /// server or corporate gateway that speaks the OpenAI API.
+    #[serde(rename = "BaseUrl")]
+    pub base_url: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -36,21 +52,43 @@ impl AppSettings {
         let config_path = "appsettings.json";
         let content = fs::read_to_string(config_path)
             .map_err(|e| format!("Failed to read {}: {}", config_path, e))?;
-        
+
         let settings: AppSettings = serde_json::from_str(&content)
             .map_err(|e| format!("Failed to parse {}: {}", config_path, e))?;
-        
+
         Ok(settings)
     }
-    
+
     pub fn get_openrouter_api_key(&self) -> Option<&str> {
         self.open_router.as_ref().map(|c| c.api_key.as_str())
     }
-    
+
+    pub fn get_openai_api_key(&self) -> Option<&str> {
+        self.open_ai.as_ref().map(|c| c.api_key.as_str())
+    }
+
+    /// The OpenRouter endpoint to use: `OpenRouter.BaseUrl` if set, otherwise
+    /// [`DEFAULT_OPENROUTER_BASE_URL`].
+    pub fn get_openrouter_base_url(&self) -> &str {
+        self.open_router
+            .as_ref()
+            .and_then(|c| c.base_url.as_deref())
+            .unwrap_or(DEFAULT_OPENROUTER_BASE_URL)
+    }
+
+    /// The OpenAI endpoint to use: `OpenAI.BaseUrl` if set, otherwise
+    /// [`DEFAULT_OPENAI_BASE_URL`].
+    pub fn get_openai_base_url(&self) -> &str {
+        self.open_ai
+            .as_ref()
+            .and_then(|c| c.base_url.as_deref())
+            .unwrap_or(DEFAULT_OPENAI_BASE_URL)
+    }
+
     pub fn get_default_model(&self) -> Option<&str> {
         self.models.as_ref().map(|m| m.default.as_str())
     }
-    
+
     pub fn get_fallback_model(&self) -> Option<&str> {
         self.models.as_ref().map(|m| m.fallback.as_str())
     }
@@ -0,0 +1,137 @@
+//! Hot-reload of [`PluginConfiguration`] from watched files.
+//!
+//! Modeled after settings hot-reloading: a [`ConfigWatcher`] keeps a registry
+//! mapping plugin name to its live [`ContextHandle`], watches each plugin's
+//! config file on disk, and on change reparses it into a
+//! [`PluginConfiguration`] and calls [`ContextHandle::update`] for the
+//! affected handle — without tearing down the underlying C# context. This
+//! lets operators change `properties` (e.g. swap a search `provider` or bump
+//! `maxResults`) and re-gate `availableFunctions` at runtime while an agent
+//! is live.
+//!
+//! Filesystem events are debounced (editors commonly emit several write
+//! events per save), and a config that fails to parse is reported through an
+//! error callback rather than applied or panicked on — the previous good
+//! config stays live.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::plugin_context::ffi_interface::ContextHandle;
+use crate::plugin_context::PluginConfiguration;
+
+/// Called with `(plugin_name, error_message)` when a watched config file
+/// changes but fails to parse into a [`PluginConfiguration`].
+pub type ConfigErrorCallback = Box<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Minimum time between two applied reloads of the same file, so a burst of
+/// filesystem events from a single save only triggers one reparse.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+struct WatchedPlugin {
+    path: PathBuf,
+    handle: ContextHandle,
+    last_applied: Option<Instant>,
+}
+
+/// Watches one or more plugin config files and keeps their live
+/// [`ContextHandle`]s in sync with changes on disk.
+pub struct ConfigWatcher {
+    registry: Arc<Mutex<HashMap<String, WatchedPlugin>>>,
+    watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Creates a watcher. `on_error` is invoked whenever a changed config
+    /// file fails to parse; the previous good config remains in effect.
+    pub fn new(on_error: ConfigErrorCallback) -> Result<Self, String> {
+        let registry: Arc<Mutex<HashMap<String, WatchedPlugin>>> = Arc::new(Mutex::new(HashMap::new()));
+        let (tx, rx) = channel();
+
+        let watcher: RecommendedWatcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to create filesystem watcher: {}", e))?;
+
+        let registry_for_thread = Arc::clone(&registry);
+        std::thread::spawn(move || {
+            for event in rx {
+                let Ok(event) = event else { continue };
+                if !matches!(event.kind, notify::EventKind::Modify(_) | notify::EventKind::Create(_)) {
+                    continue;
+                }
+                for changed_path in &event.paths {
+                    reload_affected_plugins(&registry_for_thread, changed_path, &on_error);
+                }
+            }
+        });
+
+        Ok(Self { registry, watcher })
+    }
+
+    /// Starts watching `path` for changes affecting `plugin_name`'s context,
+    /// applying updates to `handle` as they arrive.
+    pub fn watch(&self, plugin_name: &str, path: impl AsRef<Path>, handle: ContextHandle) -> Result<(), String> {
+        let path = path.as_ref().to_path_buf();
+
+        self.watcher.watch(&path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch config file '{}': {}", path.display(), e))?;
+
+        let mut registry = self.registry.lock()
+            .map_err(|_| "Config watcher registry lock poisoned".to_string())?;
+        registry.insert(plugin_name.to_string(), WatchedPlugin { path, handle, last_applied: None });
+
+        Ok(())
+    }
+
+    /// Stops watching the config file for `plugin_name` and drops its handle
+    /// from the registry (the `ContextHandle` itself is still owned by the
+    /// caller if they kept a reference elsewhere; here it's simply no longer
+    /// auto-updated).
+    pub fn unwatch(&self, plugin_name: &str) -> Result<(), String> {
+        let mut registry = self.registry.lock()
+            .map_err(|_| "Config watcher registry lock poisoned".to_string())?;
+
+        if let Some(watched) = registry.remove(plugin_name) {
+            let _ = self.watcher.unwatch(&watched.path);
+        }
+
+        Ok(())
+    }
+}
+
+fn reload_affected_plugins(
+    registry: &Arc<Mutex<HashMap<String, WatchedPlugin>>>,
+    changed_path: &Path,
+    on_error: &ConfigErrorCallback,
+) {
+    let Ok(mut registry) = registry.lock() else { return };
+
+    for (plugin_name, watched) in registry.iter_mut() {
+        if watched.path != changed_path {
+            continue;
+        }
+
+        if let Some(last) = watched.last_applied {
+            if last.elapsed() < DEBOUNCE {
+                continue;
+            }
+        }
+
+        match std::fs::read_to_string(&watched.path).map_err(|e| e.to_string())
+            .and_then(|contents| PluginConfiguration::from_json(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(config) => match watched.handle.update(&config) {
+                Ok(()) => {
+                    watched.last_applied = Some(Instant::now());
+                    println!("Reloaded config for plugin '{}' from {}", plugin_name, watched.path.display());
+                }
+                Err(e) => on_error(plugin_name, &e),
+            },
+            Err(e) => on_error(plugin_name, &format!("Failed to parse config: {}", e)),
+        }
+    }
+}
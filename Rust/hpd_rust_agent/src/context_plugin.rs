@@ -0,0 +1,291 @@
+//! Crawl-backed local file context plugin.
+//!
+//! The dynamic-configuration example (`examples/plugin_configuration_example.rs`)
+//! builds a `WebSearchPlugin` config but the crate has never shipped a
+//! plugin that actually gathers local context for the model to search.
+//! [`FileContextPlugin`] fills that gap: configured through a `crawl`
+//! sub-config ([`CrawlOptions`]) stored under the `"crawl"` property of a
+//! [`PluginConfiguration`]/[`PluginContext`] - so it round-trips through
+//! [`PluginContext::to_json`]/[`PluginContext::from_json`] like any other
+//! property - it walks the working directory (or just the opened file, when
+//! `all_files` is `false`), indexing file contents into memory up to a
+//! configurable cap, evicting the least-recently-indexed file first once
+//! that cap is reached. The model pulls indexed content into context through
+//! the `search`/`retrieve` functions below.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::plugin_context::PluginContext;
+use crate::{ai_function, hpd_plugin};
+
+/// Default cap on how much file content [`FileContextPlugin`] holds in
+/// memory at once, in megabytes.
+pub const DEFAULT_MAX_CRAWL_MEMORY_MB: u64 = 42;
+
+/// The `crawl` sub-config read off a [`PluginContext`]'s `"crawl"` property
+/// for [`FileContextPlugin`].
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct CrawlOptions {
+    /// Memory cap for indexed file content, in megabytes.
+    #[serde(default = "default_max_crawl_memory_mb", rename = "maxCrawlMemory")]
+    pub max_crawl_memory_mb: u64,
+    /// When `true`, crawl every file under the working directory; when
+    /// `false`, index only the currently opened file.
+    #[serde(default, rename = "allFiles")]
+    pub all_files: bool,
+}
+
+fn default_max_crawl_memory_mb() -> u64 {
+    DEFAULT_MAX_CRAWL_MEMORY_MB
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_crawl_memory_mb: DEFAULT_MAX_CRAWL_MEMORY_MB,
+            all_files: false,
+        }
+    }
+}
+
+impl CrawlOptions {
+    /// Reads the `crawl` property off a [`PluginContext`], falling back to
+    /// defaults if it's absent or fails to deserialize.
+    pub fn from_context(context: &PluginContext) -> Self {
+        context.get_property("crawl").unwrap_or_default()
+    }
+}
+
+/// One file's indexed content, plus the insertion sequence used to decide
+/// what to evict first.
+#[derive(Debug, Clone)]
+struct IndexedFile {
+    content: String,
+    sequence: u64,
+}
+
+/// A file-backed context store that crawls the working directory (or a
+/// single opened file) into memory and exposes it to the model through
+/// `search`/`retrieve`.
+#[derive(Debug, Default)]
+pub struct FileContextPlugin {
+    options: CrawlOptions,
+    files: HashMap<PathBuf, IndexedFile>,
+    total_bytes: u64,
+    next_sequence: u64,
+}
+
+impl FileContextPlugin {
+    /// Builds a plugin that crawls `root` (typically the working directory)
+    /// according to `options`. When `options.all_files` is `false`, only
+    /// `opened_file` (resolved relative to `root`) is indexed.
+    pub fn new(root: impl AsRef<Path>, opened_file: Option<&Path>, options: CrawlOptions) -> Self {
+        let mut plugin = Self {
+            options,
+            files: HashMap::new(),
+            total_bytes: 0,
+            next_sequence: 0,
+        };
+        plugin.crawl(root.as_ref(), opened_file);
+        plugin
+    }
+
+    /// Builds a plugin from a [`PluginContext`]'s `crawl` property, crawling
+    /// `root` the same way [`FileContextPlugin::new`] does.
+    pub fn from_context(root: impl AsRef<Path>, opened_file: Option<&Path>, context: &PluginContext) -> Self {
+        Self::new(root, opened_file, CrawlOptions::from_context(context))
+    }
+
+    /// Number of files currently indexed in memory.
+    pub fn indexed_file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    fn max_bytes(&self) -> u64 {
+        self.options.max_crawl_memory_mb.saturating_mul(1024 * 1024)
+    }
+
+    fn crawl(&mut self, root: &Path, opened_file: Option<&Path>) {
+        if self.options.all_files {
+            self.crawl_dir(root);
+        } else if let Some(file) = opened_file {
+            let path = if file.is_absolute() { file.to_path_buf() } else { root.join(file) };
+            self.index_file(&path);
+        }
+    }
+
+    fn crawl_dir(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            if self.total_bytes >= self.max_bytes() {
+                return;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                self.crawl_dir(&path);
+            } else {
+                self.index_file(&path);
+            }
+        }
+    }
+
+    fn index_file(&mut self, path: &Path) {
+        let Ok(content) = std::fs::read_to_string(path) else { return };
+        let size = content.len() as u64;
+        self.evict_to_fit(size);
+        self.total_bytes += size;
+        self.files.insert(path.to_path_buf(), IndexedFile { content, sequence: self.next_sequence });
+        self.next_sequence += 1;
+    }
+
+    /// Evicts the least-recently-indexed files (smallest `sequence` first)
+    /// until `incoming_size` more bytes fit under the configured cap.
+    fn evict_to_fit(&mut self, incoming_size: u64) {
+        let cap = self.max_bytes();
+        while self.total_bytes + incoming_size > cap && !self.files.is_empty() {
+            let Some(oldest) = self.files.iter().min_by_key(|(_, file)| file.sequence).map(|(path, _)| path.clone()) else {
+                break;
+            };
+            if let Some(removed) = self.files.remove(&oldest) {
+                self.total_bytes = self.total_bytes.saturating_sub(removed.content.len() as u64);
+            }
+        }
+    }
+}
+
+/// Snaps `index` back to the nearest char boundary at or before it.
+fn floor_to_char_boundary(content: &str, mut index: usize) -> usize {
+    while index > 0 && !content.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Snaps `index` forward to the nearest char boundary at or after it.
+fn ceil_to_char_boundary(content: &str, mut index: usize) -> usize {
+    while index < content.len() && !content.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Returns up to ~200 characters of `content` centered on the first
+/// occurrence of `query`, so `search` results carry enough context to be
+/// useful without dumping the whole file.
+fn snippet_around(content: &str, query: &str) -> Option<String> {
+    const RADIUS: usize = 100;
+    let index = content.find(query)?;
+    let start = floor_to_char_boundary(content, index.saturating_sub(RADIUS));
+    let end = ceil_to_char_boundary(content, (index + query.len() + RADIUS).min(content.len()));
+    Some(content[start..end].to_string())
+}
+
+#[hpd_plugin("FileContextPlugin", "Indexes local file contents into memory and exposes them to the model as searchable context")]
+impl FileContextPlugin {
+    #[ai_function("Search indexed file contents for a query string, returning matching file paths with a short snippet", name = "search")]
+    pub fn search(&self, query: String) -> Vec<String> {
+        self.files
+            .iter()
+            .filter_map(|(path, file)| {
+                let snippet = snippet_around(&file.content, &query)?;
+                Some(format!("{}: {}", path.display(), snippet))
+            })
+            .collect()
+    }
+
+    #[ai_function("Retrieve the full indexed content of a file by path", name = "retrieve")]
+    pub fn retrieve(&self, path: String) -> Result<String, String> {
+        self.files
+            .get(Path::new(&path))
+            .map(|file| file.content.clone())
+            .ok_or_else(|| format!("No indexed content for '{}'", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_crawl_options_from_context_defaults_when_absent() {
+        let context = PluginContext::new();
+        let options = CrawlOptions::from_context(&context);
+        assert_eq!(options.max_crawl_memory_mb, DEFAULT_MAX_CRAWL_MEMORY_MB);
+        assert!(!options.all_files);
+    }
+
+    #[test]
+    fn test_crawl_options_round_trip_through_plugin_context_json() {
+        let mut context = PluginContext::new();
+        context.set_property("crawl", CrawlOptions { max_crawl_memory_mb: 7, all_files: true }).unwrap();
+
+        let json = context.to_json().unwrap();
+        let restored = PluginContext::from_json(&json).unwrap();
+        let options = CrawlOptions::from_context(&restored);
+
+        assert_eq!(options.max_crawl_memory_mb, 7);
+        assert!(options.all_files);
+    }
+
+    #[test]
+    fn test_new_with_all_files_false_indexes_only_opened_file() {
+        let dir = std::env::temp_dir();
+        write_temp_file("hpd_context_plugin_test_a.txt", "alpha contents");
+        let opened = write_temp_file("hpd_context_plugin_test_b.txt", "bravo contents");
+
+        let plugin = FileContextPlugin::new(&dir, Some(Path::new("hpd_context_plugin_test_b.txt")), CrawlOptions::default());
+
+        assert_eq!(plugin.indexed_file_count(), 1);
+        assert_eq!(plugin.retrieve(opened.to_str().unwrap().to_string()).unwrap(), "bravo contents");
+
+        std::fs::remove_file(dir.join("hpd_context_plugin_test_a.txt")).ok();
+        std::fs::remove_file(opened).ok();
+    }
+
+    #[test]
+    fn test_evict_to_fit_drops_oldest_file_first() {
+        let dir = std::env::temp_dir().join("hpd_context_plugin_test_evict");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("first.txt"), "a".repeat(10)).unwrap();
+        std::fs::write(dir.join("second.txt"), "b".repeat(10)).unwrap();
+
+        let options = CrawlOptions { max_crawl_memory_mb: 0, all_files: true };
+        let mut plugin = FileContextPlugin::new(&dir, None, CrawlOptions::default());
+        plugin.options = options; // apply a cap too small to hold both files
+        plugin.files.clear();
+        plugin.total_bytes = 0;
+        plugin.next_sequence = 0;
+        plugin.index_file(&dir.join("first.txt"));
+        plugin.index_file(&dir.join("second.txt"));
+
+        // With a zero byte cap, indexing `second.txt` evicts the older
+        // `first.txt`, so only the most recently indexed file survives.
+        assert_eq!(plugin.indexed_file_count(), 1);
+        assert!(plugin.retrieve(dir.join("second.txt").to_str().unwrap().to_string()).is_ok());
+        assert!(plugin.retrieve(dir.join("first.txt").to_str().unwrap().to_string()).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_search_returns_snippet_for_matching_files() {
+        let dir = std::env::temp_dir().join("hpd_context_plugin_test_search");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("needle.txt"), "some text with a needle in it").unwrap();
+
+        let plugin = FileContextPlugin::new(&dir, None, CrawlOptions { max_crawl_memory_mb: 1, all_files: true });
+        let results = plugin.search("needle".to_string());
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].contains("needle"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
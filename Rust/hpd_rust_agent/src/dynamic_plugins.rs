@@ -0,0 +1,232 @@
+//! Runtime loading of external plugins from shared libraries.
+//!
+//! Unlike the macro-registered plugins in [`crate::example_plugins`], plugins
+//! loaded through this module live in their own `.so`/`.dll`/`.dylib` and are
+//! discovered with `dlopen` at runtime, so they can be dropped next to the
+//! host binary without recompiling the agent.
+//!
+//! Plugin authors build a cdylib that exports a single C-ABI entry point:
+//!
+//! ```c
+//! const HpdPluginRegistrationFfi* hpd_plugin_register(void);
+//! ```
+//!
+//! `HpdPluginRegistrationFfi` is a plain struct of null-terminated UTF-8
+//! C strings owned by the plugin for the lifetime of the process (a `static`
+//! or leaked allocation on the plugin side is the simplest way to satisfy
+//! this):
+//!
+//! ```c
+//! typedef struct {
+//!     const char* name;              // plugin name
+//!     const char* description;       // plugin description
+//!     const char* functions_json;    // JSON array of [function_name, wrapper_name] pairs
+//!     const char* schemas_json;      // JSON object of function_name -> schema string
+//! } HpdPluginRegistrationFfi;
+//! ```
+//!
+//! Every `wrapper_name` named in a `functions_json` pair must be callable
+//! through two more C-ABI exports, the native-dylib equivalent of
+//! [`wasm_plugins`](crate::wasm_plugins)'s `call_function` guest contract:
+//!
+//! ```c
+//! char* hpd_plugin_call(const char* wrapper_name, const char* args_json);
+//! void hpd_plugin_free_string(char* s);
+//! ```
+//!
+//! `hpd_plugin_call` invokes `wrapper_name` with a JSON args string and
+//! returns a newly allocated, null-terminated JSON string of shape
+//! `{"Ok": ...}` or `{"Err": ...}`; the host takes ownership of the returned
+//! pointer and releases it with `hpd_plugin_free_string`.
+
+use std::collections::HashMap;
+use std::os::raw::c_char;
+use std::sync::Mutex;
+
+use libloading::Library;
+use once_cell::sync::Lazy;
+
+use crate::plugin_context::LossyString;
+use crate::plugins::{register_async_executor, register_plugin, unregister_plugin, PluginRegistration};
+
+/// The C-ABI shape a plugin library hands back from `hpd_plugin_register`.
+#[repr(C)]
+pub struct PluginRegistrationFfi {
+    pub name: *const c_char,
+    pub description: *const c_char,
+    pub functions_json: *const c_char,
+    pub schemas_json: *const c_char,
+}
+
+type RegisterFn = unsafe extern "C" fn() -> *const PluginRegistrationFfi;
+type CallFn = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut c_char;
+type FreeStringFn = unsafe extern "C" fn(*mut c_char);
+
+/// A loaded plugin library plus the name it registered under, so
+/// [`unload_plugin_library`] can undo that registration before the library
+/// is unmapped.
+struct LoadedLibrary {
+    library: Library,
+    plugin_name: String,
+}
+
+/// Libraries currently loaded, keyed by the path they were loaded from.
+/// The `Library` handle must stay alive for as long as any function pointer
+/// or registration resolved from it might still be in use.
+static LOADED_LIBRARIES: Lazy<Mutex<HashMap<String, LoadedLibrary>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Loads a plugin from a shared library at `path` and registers it with the
+/// global plugin registry.
+///
+/// Returns the name of the plugin that was registered.
+pub fn load_plugin_library(path: &str) -> Result<String, String> {
+    if LOADED_LIBRARIES.lock().map_err(|_| "Plugin library registry lock poisoned".to_string())?.contains_key(path) {
+        return Err(format!("Plugin library already loaded: {}", path));
+    }
+
+    let library = unsafe {
+        Library::new(path).map_err(|e| format!("Failed to load plugin library '{}': {}", path, e))?
+    };
+
+    let registration = unsafe {
+        let register: libloading::Symbol<RegisterFn> = library
+            .get(b"hpd_plugin_register\0")
+            .map_err(|e| format!("Plugin library '{}' is missing hpd_plugin_register: {}", path, e))?;
+
+        let raw = register();
+        if raw.is_null() {
+            return Err(format!("Plugin library '{}' returned a null registration", path));
+        }
+        marshal_registration(&*raw)?
+    };
+
+    let name = registration.name.clone();
+
+    for (function_name, wrapper_name) in &registration.functions {
+        let path = path.to_string();
+        let wrapper_name = wrapper_name.clone();
+
+        register_async_executor(
+            function_name.clone(),
+            Box::new(move |args_json| {
+                let path = path.clone();
+                let wrapper_name = wrapper_name.clone();
+                Box::pin(async move { call_native_function(&path, &wrapper_name, &args_json) })
+            }),
+        );
+    }
+
+    register_plugin(registration);
+
+    if let Ok(mut libraries) = LOADED_LIBRARIES.lock() {
+        libraries.insert(path.to_string(), LoadedLibrary { library, plugin_name: name.clone() });
+    }
+
+    println!("Loaded external plugin '{}' from {}", name, path);
+    Ok(name)
+}
+
+/// Unloads the plugin library at `path`, removing its registrations and
+/// executors, then closes the `dlopen` handle.
+pub fn unload_plugin_library(path: &str) -> Result<(), String> {
+    let mut libraries = LOADED_LIBRARIES.lock()
+        .map_err(|_| "Plugin library registry lock poisoned".to_string())?;
+
+    let loaded = libraries.remove(path)
+        .ok_or_else(|| format!("No plugin library loaded from: {}", path))?;
+
+    // Remove the plugin's registrations/executors before dropping the
+    // library below - once the library is unmapped, any lingering executor
+    // closure would dlsym into unmapped memory.
+    unregister_plugin(&loaded.plugin_name);
+
+    drop(loaded.library);
+
+    Ok(())
+}
+
+/// Invokes `wrapper_name` inside the library loaded from `path` via that
+/// library's `hpd_plugin_call` export, passing `args_json` and returning its
+/// `Result`-shaped JSON response. Re-resolves the library by path on every
+/// call, mirroring [`wasm_plugins::call_wasm_function`](crate::wasm_plugins)'s
+/// per-call lookup for its own backend.
+fn call_native_function(path: &str, wrapper_name: &str, args_json: &str) -> Result<String, String> {
+    let libraries = LOADED_LIBRARIES.lock()
+        .map_err(|_| "Plugin library registry lock poisoned".to_string())?;
+    let loaded = libraries.get(path)
+        .ok_or_else(|| format!("Plugin library '{}' is no longer loaded", path))?;
+
+    let (call_fn, free_fn): (CallFn, FreeStringFn) = unsafe {
+        let call: libloading::Symbol<CallFn> = loaded.library.get(b"hpd_plugin_call\0")
+            .map_err(|e| format!("Plugin library '{}' is missing hpd_plugin_call: {}", path, e))?;
+        let free: libloading::Symbol<FreeStringFn> = loaded.library.get(b"hpd_plugin_free_string\0")
+            .map_err(|e| format!("Plugin library '{}' is missing hpd_plugin_free_string: {}", path, e))?;
+        (*call, *free)
+    };
+
+    let wrapper_name_c = std::ffi::CString::new(wrapper_name)
+        .map_err(|e| format!("Function name '{}' contains an interior null byte: {}", wrapper_name, e))?;
+    let args_json_c = std::ffi::CString::new(args_json)
+        .map_err(|e| format!("Arguments for '{}' contain an interior null byte: {}", wrapper_name, e))?;
+
+    let result_ptr = unsafe { call_fn(wrapper_name_c.as_ptr(), args_json_c.as_ptr()) };
+    if result_ptr.is_null() {
+        return Err(format!("Plugin function '{}' returned a null result", wrapper_name));
+    }
+
+    let response = unsafe {
+        let response: String = LossyString::from_c_str(result_ptr).into();
+        free_fn(result_ptr);
+        response
+    };
+
+    let parsed: serde_json::Value = serde_json::from_str(&response)
+        .map_err(|e| format!("Invalid response JSON from plugin function '{}': {}", wrapper_name, e))?;
+
+    match parsed {
+        serde_json::Value::Object(mut object) if object.contains_key("Ok") => {
+            Ok(object.remove("Ok").unwrap().to_string())
+        }
+        serde_json::Value::Object(mut object) if object.contains_key("Err") => {
+            Err(object.remove("Err").as_ref().and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| "plugin function failed".to_string()))
+        }
+        other => Err(format!("Unexpected response shape from plugin function '{}': {}", wrapper_name, other)),
+    }
+}
+
+/// Converts the raw FFI struct into an owned [`PluginRegistration`] by
+/// copying every string across the FFI boundary.
+unsafe fn marshal_registration(raw: &PluginRegistrationFfi) -> Result<PluginRegistration, String> {
+    let name = read_c_str(raw.name, "name")?;
+    let description = read_c_str(raw.description, "description")?;
+    let functions_json = read_c_str(raw.functions_json, "functions_json")?;
+    let schemas_json = read_c_str(raw.schemas_json, "schemas_json")?;
+
+    let functions: Vec<(String, String)> = serde_json::from_str(&functions_json)
+        .map_err(|e| format!("Invalid functions_json from plugin library: {}", e))?;
+    let schemas: HashMap<String, String> = serde_json::from_str(&schemas_json)
+        .map_err(|e| format!("Invalid schemas_json from plugin library: {}", e))?;
+
+    Ok(PluginRegistration {
+        name,
+        description,
+        functions,
+        schemas,
+        is_unique: true,
+        examples: HashMap::new(),
+    })
+}
+
+/// Reads a non-null C string field from the plugin's registration struct.
+/// Invalid UTF-8 (a misbehaving or malicious plugin) is replaced with U+FFFD
+/// rather than failing registration outright — only a genuinely null
+/// pointer is treated as an error.
+unsafe fn read_c_str(ptr: *const c_char, field: &str) -> Result<String, String> {
+    if ptr.is_null() {
+        return Err(format!("Plugin library registration field '{}' was null", field));
+    }
+    Ok(LossyString::from_c_str(ptr).into())
+}
@@ -0,0 +1,219 @@
+//! Structured evaluation harness for [`Conversation`] test cases.
+//!
+//! `function_call_test.rs` used to judge correctness by substring checks
+//! like `response.contains("8")` and counting how many "expected results"
+//! strings showed up anywhere in the response text - brittle, since it can't
+//! tell a correct call from a coincidentally-matching digit elsewhere in the
+//! output. [`ConversationEval`] replaces that: it drives
+//! [`Conversation::send_detailed`] for each [`EvalCase`], then checks its
+//! [`Assertion`]s against the actual dispatched [`ToolCall`]s and the
+//! conversation's final text, producing an [`EvalReport`] that serializes
+//! cleanly for CI consumption.
+
+use serde_json::Value as JsonValue;
+
+use crate::conversation::{Conversation, ToolCall};
+
+/// One thing an [`EvalCase`] run must satisfy.
+#[derive(Debug, Clone)]
+pub enum Assertion {
+    /// A call to `name` with exactly `args` must appear among the dispatched
+    /// tool calls.
+    Call { name: String, args: JsonValue },
+    /// The conversation's final text, parsed as JSON (falling back to a
+    /// plain string if it doesn't parse), must equal `value`.
+    FinalValue(JsonValue),
+}
+
+/// Asserts that a call to `name` with `args` was dispatched during the case.
+pub fn expect_call(name: impl Into<String>, args: JsonValue) -> Assertion {
+    Assertion::Call { name: name.into(), args }
+}
+
+/// Asserts that the conversation's final text equals `value` once parsed
+/// (e.g. `expect_final_value(36)` matches a final text of `"36"`).
+pub fn expect_final_value(value: impl Into<JsonValue>) -> Assertion {
+    Assertion::FinalValue(value.into())
+}
+
+/// One test case: a prompt to send plus the assertions its run must satisfy.
+pub struct EvalCase {
+    pub name: String,
+    pub prompt: String,
+    pub assertions: Vec<Assertion>,
+}
+
+impl EvalCase {
+    pub fn new(name: impl Into<String>, prompt: impl Into<String>, assertions: Vec<Assertion>) -> Self {
+        Self { name: name.into(), prompt: prompt.into(), assertions }
+    }
+}
+
+/// The outcome of checking a single [`Assertion`] against an [`EvalCase`]'s
+/// run.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AssertionResult {
+    pub description: String,
+    pub passed: bool,
+}
+
+/// The full recorded outcome of running one [`EvalCase`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EvalCaseReport {
+    pub name: String,
+    pub prompt: String,
+    pub final_text: Option<String>,
+    pub calls: Vec<ToolCall>,
+    pub assertions: Vec<AssertionResult>,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+/// The structured result of a full [`ConversationEval::run`], serializable
+/// for CI consumption.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EvalReport {
+    pub cases: Vec<EvalCaseReport>,
+}
+
+impl EvalReport {
+    /// Whether every case's every assertion passed.
+    pub fn all_passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+}
+
+/// Drives a [`Conversation`] through a list of [`EvalCase`]s, recording each
+/// dispatched [`ToolCall`] and checking it against that case's assertions.
+pub struct ConversationEval<'a> {
+    conversation: &'a Conversation,
+    cases: Vec<EvalCase>,
+}
+
+impl<'a> ConversationEval<'a> {
+    pub fn new(conversation: &'a Conversation, cases: Vec<EvalCase>) -> Self {
+        Self { conversation, cases }
+    }
+
+    /// Runs every case in order against the same conversation, returning a
+    /// structured [`EvalReport`].
+    pub fn run(&self) -> EvalReport {
+        let cases = self
+            .cases
+            .iter()
+            .map(|case| self.run_case(case))
+            .collect();
+
+        EvalReport { cases }
+    }
+
+    fn run_case(&self, case: &EvalCase) -> EvalCaseReport {
+        match self.conversation.send_detailed(&case.prompt) {
+            Ok(result) => {
+                let assertions: Vec<AssertionResult> = case
+                    .assertions
+                    .iter()
+                    .map(|assertion| check_assertion(assertion, &result.calls, &result.text))
+                    .collect();
+                let passed = assertions.iter().all(|assertion| assertion.passed);
+
+                EvalCaseReport {
+                    name: case.name.clone(),
+                    prompt: case.prompt.clone(),
+                    final_text: Some(result.text),
+                    calls: result.calls,
+                    assertions,
+                    passed,
+                    error: None,
+                }
+            }
+            Err(error) => EvalCaseReport {
+                name: case.name.clone(),
+                prompt: case.prompt.clone(),
+                final_text: None,
+                calls: Vec::new(),
+                assertions: Vec::new(),
+                passed: false,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+fn check_assertion(assertion: &Assertion, calls: &[ToolCall], final_text: &str) -> AssertionResult {
+    match assertion {
+        Assertion::Call { name, args } => {
+            let passed = calls.iter().any(|call| &call.name == name && &call.arguments == args);
+            AssertionResult {
+                description: format!("expect_call({}, {})", name, args),
+                passed,
+            }
+        }
+        Assertion::FinalValue(expected) => {
+            let actual = serde_json::from_str::<JsonValue>(final_text)
+                .unwrap_or_else(|_| JsonValue::String(final_text.to_string()));
+            AssertionResult {
+                description: format!("expect_final_value({})", expected),
+                passed: &actual == expected,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::conversation::{FunctionCallRequest, MockModelBackend, ModelTurn};
+    use std::sync::Arc;
+
+    fn dummy_agent() -> crate::agent::Agent {
+        AgentBuilder::new("Eval Test Agent")
+            .with_backend(Arc::new(crate::ffi_backend::MockBackend::new()))
+            .build()
+            .expect("Failed to build test agent")
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_eval_reports_pass_when_call_and_final_value_match() {
+        let backend = Arc::new(MockModelBackend::new(vec![
+            ModelTurn {
+                text: None,
+                function_calls: vec![FunctionCallRequest {
+                    id: "1".to_string(),
+                    name: "add".to_string(),
+                    arguments: serde_json::json!({"a": 1, "b": 2}),
+                }],
+            },
+            ModelTurn { text: Some("3".to_string()), function_calls: vec![] },
+        ]));
+
+        let conversation = Conversation::with_backend(vec![dummy_agent()], backend).unwrap();
+        let cases = vec![EvalCase::new(
+            "basic add",
+            "add 1 and 2",
+            vec![expect_call("add", serde_json::json!({"a": 1, "b": 2})), expect_final_value(3)],
+        )];
+
+        let report = ConversationEval::new(&conversation, cases).run();
+
+        assert!(report.all_passed());
+        assert_eq!(report.cases[0].calls.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_eval_reports_failure_when_final_value_mismatches() {
+        let backend = Arc::new(MockModelBackend::new(vec![ModelTurn {
+            text: Some("42".to_string()),
+            function_calls: vec![],
+        }]));
+
+        let conversation = Conversation::with_backend(vec![dummy_agent()], backend).unwrap();
+        let cases = vec![EvalCase::new("wrong answer", "what is it", vec![expect_final_value(3)])];
+
+        let report = ConversationEval::new(&conversation, cases).run();
+
+        assert!(!report.all_passed());
+        assert!(!report.cases[0].assertions[0].passed);
+    }
+}
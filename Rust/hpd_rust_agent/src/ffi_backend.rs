@@ -0,0 +1,243 @@
+//! Trait-based abstraction over the C# FFI surface `ContextHandle` and
+//! `Agent` depend on.
+//!
+//! Every cross-FFI call (get plugin metadata, create/update/drop a context,
+//! evaluate a condition, filter available functions, create/destroy an
+//! agent) used to go straight to a free function in `crate::ffi`, which made
+//! these paths untestable without a loaded C# host. [`FfiBackend`] mirrors
+//! that surface as an object-safe trait; [`CSharpBackend`] is the real
+//! implementation backed by `crate::ffi`, and [`MockBackend`] is an
+//! in-memory stand-in that resolves conditions against
+//! [`PluginContext`](crate::plugin_context::PluginContext), returns
+//! synthetic [`DynamicFunctionMetadata`](crate::plugin_context::DynamicFunctionMetadata),
+//! and hands out preloaded metadata — letting tests exercise
+//! filtering/condition logic and context-update cache invalidation
+//! deterministically by swapping in a mock at construction time, the same
+//! way a `TimeMock` stands in for a real clock.
+
+use std::collections::HashMap;
+use std::ffi::{c_void, CString};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use serde_json::Value as JsonValue;
+
+use crate::plugin_context::{DynamicFunctionMetadata, PluginConfiguration, PluginContext};
+use crate::wire::WireEncoding;
+
+/// The FFI operations `ContextHandle` and `Agent` need from a backend.
+///
+/// Handles are opaque `*mut c_void` values; a backend is free to give them
+/// whatever meaning it likes (a real pointer into the C# runtime for
+/// [`CSharpBackend`], or a synthetic counter for [`MockBackend`]).
+pub trait FfiBackend: Send + Sync {
+    fn get_plugin_metadata(&self) -> Result<JsonValue, String>;
+    fn create_context_handle(&self, config: &PluginConfiguration) -> Result<*mut c_void, String>;
+    fn update_context_handle(&self, handle: *mut c_void, config: &PluginConfiguration) -> Result<(), String>;
+    fn evaluate_precompiled_condition(&self, plugin_type: &str, function_name: &str, handle: *mut c_void) -> bool;
+    fn filter_available_functions(&self, plugin_type: &str, handle: *mut c_void) -> Result<Vec<DynamicFunctionMetadata>, String>;
+    fn destroy_context_handle(&self, handle: *mut c_void);
+    /// `config_payload`/`plugins_payload` are encoded with `encoding` (see
+    /// [`crate::wire`]); `encoding` is also passed alongside them, out of
+    /// band, so the C# side knows which decoder to use before it can parse
+    /// the payloads themselves.
+    fn create_agent(&self, config_payload: &str, plugins_payload: &str, encoding: WireEncoding) -> Result<*mut c_void, String>;
+    fn destroy_agent(&self, handle: *mut c_void);
+}
+
+/// Real backend: forwards every call across the FFI boundary to the C# host.
+pub struct CSharpBackend;
+
+impl FfiBackend for CSharpBackend {
+    fn get_plugin_metadata(&self) -> Result<JsonValue, String> {
+        let result_ptr = unsafe { crate::ffi::get_plugin_metadata_json() };
+        if result_ptr.is_null() {
+            return Err("FFI function returned null".to_string());
+        }
+
+        let json_str = unsafe { crate::plugin_context::ffi_interface::decode_c_str(result_ptr)? };
+        let metadata = crate::plugin_context::LossyJson::parse(&json_str)?;
+
+        unsafe { crate::ffi::free_string(result_ptr as *mut c_void) };
+
+        Ok(metadata)
+    }
+
+    fn create_context_handle(&self, config: &PluginConfiguration) -> Result<*mut c_void, String> {
+        let json = config.to_json().map_err(|e| format!("Failed to serialize config: {}", e))?;
+        let c_json = CString::new(json).map_err(|e| format!("Failed to create CString: {}", e))?;
+
+        let handle = unsafe { crate::ffi::create_context_handle(c_json.as_ptr()) };
+        if handle.is_null() {
+            Err("Failed to create context handle".to_string())
+        } else {
+            Ok(handle)
+        }
+    }
+
+    fn update_context_handle(&self, handle: *mut c_void, config: &PluginConfiguration) -> Result<(), String> {
+        let json = config.to_json().map_err(|e| format!("Failed to serialize config: {}", e))?;
+        let c_json = CString::new(json).map_err(|e| format!("Failed to create CString: {}", e))?;
+
+        if unsafe { crate::ffi::update_context_handle(handle, c_json.as_ptr()) } {
+            Ok(())
+        } else {
+            Err("Failed to update context handle".to_string())
+        }
+    }
+
+    fn evaluate_precompiled_condition(&self, plugin_type: &str, function_name: &str, handle: *mut c_void) -> bool {
+        let (Ok(c_plugin_type), Ok(c_function_name)) = (CString::new(plugin_type), CString::new(function_name)) else {
+            return false;
+        };
+
+        unsafe {
+            crate::ffi::evaluate_precompiled_condition(c_plugin_type.as_ptr(), c_function_name.as_ptr(), handle)
+        }
+    }
+
+    fn filter_available_functions(&self, plugin_type: &str, handle: *mut c_void) -> Result<Vec<DynamicFunctionMetadata>, String> {
+        let c_plugin_type = CString::new(plugin_type)
+            .map_err(|e| format!("Failed to create CString for plugin type: {}", e))?;
+
+        let result_ptr = unsafe { crate::ffi::filter_available_functions(c_plugin_type.as_ptr(), handle) };
+        if result_ptr.is_null() {
+            return Err("FFI function returned null".to_string());
+        }
+
+        let json_str = unsafe { crate::plugin_context::ffi_interface::decode_c_str(result_ptr)? };
+        let metadata = crate::plugin_context::LossyJson::parse(&json_str)?;
+
+        unsafe { crate::ffi::free_string(result_ptr as *mut c_void) };
+
+        Ok(metadata)
+    }
+
+    fn destroy_context_handle(&self, handle: *mut c_void) {
+        unsafe { crate::ffi::destroy_context_handle(handle) };
+    }
+
+    fn create_agent(&self, config_payload: &str, plugins_payload: &str, encoding: WireEncoding) -> Result<*mut c_void, String> {
+        let c_config = CString::new(config_payload).map_err(|e| format!("Failed to create CString from config: {}", e))?;
+        let c_plugins = CString::new(plugins_payload).map_err(|e| format!("Failed to create CString for plugins: {}", e))?;
+
+        let handle = unsafe {
+            crate::ffi::create_agent_with_plugins(c_config.as_ptr(), c_plugins.as_ptr(), encoding.into())
+        };
+        if handle.is_null() {
+            Err("Failed to create agent on C# side.".to_string())
+        } else {
+            Ok(handle)
+        }
+    }
+
+    fn destroy_agent(&self, handle: *mut c_void) {
+        unsafe { crate::ffi::destroy_agent(handle) };
+    }
+}
+
+struct MockEntry {
+    config: PluginConfiguration,
+}
+
+/// In-memory backend for tests. Handles are synthetic counters rather than
+/// real pointers; conditions are resolved with the native
+/// [`crate::condition`] engine against the stored config's properties, and
+/// `filter_available_functions` synthesizes one [`DynamicFunctionMetadata`]
+/// per entry in `available_functions` (or an empty list if none were set).
+/// `get_plugin_metadata` returns whatever was preloaded with
+/// [`MockBackend::with_metadata`], so a test can assert against known plugin
+/// metadata without a live C# process.
+#[derive(Default)]
+pub struct MockBackend {
+    entries: Mutex<HashMap<usize, MockEntry>>,
+    metadata: Mutex<JsonValue>,
+}
+
+static NEXT_MOCK_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preloads the value `get_plugin_metadata` will return.
+    pub fn with_metadata(self, metadata: JsonValue) -> Self {
+        if let Ok(mut current) = self.metadata.lock() {
+            *current = metadata;
+        }
+        self
+    }
+
+    fn config_for(&self, handle: *mut c_void) -> Option<PluginConfiguration> {
+        self.entries.lock().ok()?
+            .get(&(handle as usize))
+            .map(|entry| entry.config.clone())
+    }
+}
+
+impl FfiBackend for MockBackend {
+    fn get_plugin_metadata(&self) -> Result<JsonValue, String> {
+        Ok(self.metadata.lock().map_err(|_| "Mock backend lock poisoned".to_string())?.clone())
+    }
+
+    fn create_context_handle(&self, config: &PluginConfiguration) -> Result<*mut c_void, String> {
+        let key = NEXT_MOCK_HANDLE.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, MockEntry { config: config.clone() });
+        }
+        Ok(key as *mut c_void)
+    }
+
+    fn update_context_handle(&self, handle: *mut c_void, config: &PluginConfiguration) -> Result<(), String> {
+        let mut entries = self.entries.lock().map_err(|_| "Mock backend lock poisoned".to_string())?;
+        match entries.get_mut(&(handle as usize)) {
+            Some(entry) => {
+                entry.config = config.clone();
+                Ok(())
+            }
+            None => Err("No such mock context handle".to_string()),
+        }
+    }
+
+    fn evaluate_precompiled_condition(&self, plugin_type: &str, function_name: &str, handle: *mut c_void) -> bool {
+        let Some(config) = self.config_for(handle) else { return false };
+        let Some(condition) = crate::condition::condition_source_for(plugin_type, function_name) else { return false };
+        let Some(ast) = crate::condition::compile_condition(plugin_type, function_name, &condition) else { return false };
+
+        let context = PluginContext { properties: config.properties };
+        crate::condition::evaluate(&ast, &context)
+    }
+
+    fn filter_available_functions(&self, plugin_type: &str, handle: *mut c_void) -> Result<Vec<DynamicFunctionMetadata>, String> {
+        let config = self.config_for(handle).ok_or_else(|| "No such mock context handle".to_string())?;
+        let names = config.available_functions.clone().unwrap_or_default();
+
+        Ok(names.into_iter().map(|name| {
+            let is_available = crate::condition::condition_source_for(plugin_type, &name)
+                .and_then(|condition| crate::condition::compile_condition(plugin_type, &name, &condition))
+                .map(|ast| crate::condition::evaluate(&ast, &PluginContext { properties: config.properties.clone() }))
+                .unwrap_or(true);
+
+            DynamicFunctionMetadata {
+                name: name.clone(),
+                resolved_description: format!("Synthetic metadata for {}", name),
+                schema: HashMap::new(),
+                is_available,
+                requires_permission: false,
+            }
+        }).collect())
+    }
+
+    fn destroy_context_handle(&self, handle: *mut c_void) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(&(handle as usize));
+        }
+    }
+
+    fn create_agent(&self, _config_payload: &str, _plugins_payload: &str, _encoding: WireEncoding) -> Result<*mut c_void, String> {
+        Ok(NEXT_MOCK_HANDLE.fetch_add(1, Ordering::SeqCst) as *mut c_void)
+    }
+
+    fn destroy_agent(&self, _handle: *mut c_void) {}
+}
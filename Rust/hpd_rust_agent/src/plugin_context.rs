@@ -59,6 +59,76 @@ impl PluginConfiguration {
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
+
+    /// Converts this configuration to a TOML string.
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize config to TOML: {}", e))
+    }
+
+    /// Creates a configuration from a TOML string.
+    pub fn from_toml(toml_str: &str) -> Result<Self, String> {
+        toml::from_str(toml_str).map_err(|e| format!("Failed to parse config from TOML: {}", e))
+    }
+
+    /// Loads `path` as a [`ConfigManifest`] and deep-merges its `base`
+    /// section with the `[env.<env>]` override section, if present.
+    ///
+    /// Overrides replace individual `properties` keys (rather than
+    /// discarding the base's other properties) and replace
+    /// `availableFunctions` wholesale when the environment specifies it, so
+    /// the same plugin manifest can ship dev/staging/prod property values
+    /// (API providers, result limits, permission flags) selected at load
+    /// time.
+    pub fn from_manifest(path: &str, env: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read plugin config manifest '{}': {}", path, e))?;
+
+        let manifest: ConfigManifest = toml::from_str(&contents)
+            .map_err(|e| format!("Failed to parse plugin config manifest '{}': {}", path, e))?;
+
+        let mut merged = manifest.base;
+
+        if let Some(overrides) = manifest.env.get(env) {
+            merged.properties.extend(overrides.properties.clone());
+            if let Some(functions) = &overrides.available_functions {
+                merged.available_functions = Some(functions.clone());
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+/// A config manifest holding a base [`PluginConfiguration`] plus named
+/// environment overrides, e.g.:
+///
+/// ```toml
+/// [base]
+/// pluginName = "WebSearchPlugin"
+/// contextType = "WebSearchPluginMetadataContext"
+///
+/// [base.properties]
+/// provider = "Tavily"
+/// maxResults = 5
+///
+/// [env.prod.properties]
+/// provider = "Bing"
+/// maxResults = 20
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigManifest {
+    pub base: PluginConfiguration,
+    #[serde(default)]
+    pub env: HashMap<String, ConfigEnvOverride>,
+}
+
+/// A single named environment's overrides within a [`ConfigManifest`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConfigEnvOverride {
+    #[serde(default, rename = "properties")]
+    pub properties: HashMap<String, serde_json::Value>,
+    #[serde(default, rename = "availableFunctions")]
+    pub available_functions: Option<Vec<String>>,
 }
 
 /// Metadata about a plugin function that has been dynamically resolved.
@@ -147,90 +217,232 @@ impl Default for PluginContext {
     }
 }
 
+/// How a C string crossing the FFI boundary is decoded to UTF-8.
+///
+/// AI-generated or user-supplied content occasionally produces invalid byte
+/// sequences or lone UTF-16 surrogates by the time it reaches Rust. `Strict`
+/// preserves the original behavior (hard error on any invalid sequence);
+/// `Lossy` (the default) replaces malformed sequences with U+FFFD so a single
+/// bad description doesn't take down metadata/condition evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8DecodeMode {
+    Strict,
+    Lossy,
+}
+
+static FFI_UTF8_MODE: std::sync::Mutex<Utf8DecodeMode> = std::sync::Mutex::new(Utf8DecodeMode::Lossy);
+
+/// Sets the process-wide strict-vs-lossy toggle for decoding C strings
+/// returned across the FFI boundary. Defaults to [`Utf8DecodeMode::Lossy`].
+pub fn set_ffi_utf8_mode(mode: Utf8DecodeMode) {
+    if let Ok(mut current) = FFI_UTF8_MODE.lock() {
+        *current = mode;
+    }
+}
+
+fn ffi_utf8_mode() -> Utf8DecodeMode {
+    FFI_UTF8_MODE.lock().map(|mode| *mode).unwrap_or(Utf8DecodeMode::Lossy)
+}
+
+/// A JSON deserialization wrapper tolerant of unpaired UTF-16 surrogate
+/// escapes (`\uD800`-`\uDFFF` not forming a valid pair), which `serde_json`
+/// otherwise rejects outright since a Rust `String` can't hold them. Falls
+/// back to replacing lone surrogates with `�` and retrying before
+/// giving up.
+pub struct LossyJson;
+
+impl LossyJson {
+    pub fn parse<T: serde::de::DeserializeOwned>(json_str: &str) -> Result<T, String> {
+        match serde_json::from_str::<T>(json_str) {
+            Ok(value) => Ok(value),
+            Err(strict_err) => {
+                let sanitized = sanitize_lone_surrogates(json_str);
+                serde_json::from_str::<T>(&sanitized)
+                    .map_err(|_| format!("Failed to parse JSON: {}", strict_err))
+            }
+        }
+    }
+}
+
+/// Replaces any `\uXXXX` escape forming an unpaired (lone) UTF-16 surrogate
+/// with `�`, leaving valid surrogate pairs and everything else intact.
+pub(crate) fn sanitize_lone_surrogates(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 6 <= bytes.len() && bytes[i + 1] == b'u' {
+            if let Ok(code) = u16::from_str_radix(&input[i + 2..i + 6], 16) {
+                if (0xD800..=0xDBFF).contains(&code) {
+                    // High surrogate: valid only if immediately followed by a low surrogate.
+                    if i + 12 <= bytes.len() && &input[i + 6..i + 8] == "\\u" {
+                        if let Ok(low) = u16::from_str_radix(&input[i + 8..i + 12], 16) {
+                            if (0xDC00..=0xDFFF).contains(&low) {
+                                out.push_str(&input[i..i + 12]);
+                                i += 12;
+                                continue;
+                            }
+                        }
+                    }
+                    out.push_str("\\ufffd");
+                    i += 6;
+                    continue;
+                } else if (0xDC00..=0xDFFF).contains(&code) {
+                    // Lone low surrogate with no preceding high surrogate.
+                    out.push_str("\\ufffd");
+                    i += 6;
+                    continue;
+                }
+            }
+        }
+
+        let char_len = input[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+        out.push_str(&input[i..i + char_len]);
+        i += char_len;
+    }
+
+    out
+}
+
+/// A `String` that's always recoverable from a raw C string, even when the
+/// bytes aren't valid UTF-8.
+///
+/// Use [`LossyString::from_c_str`] at a raw `CStr` boundary instead of
+/// `CStr::from_ptr(ptr).to_str().unwrap()`, which panics on malformed bytes
+/// a C# caller can readily produce. As a struct field it derefs to `&str`
+/// and deserializes exactly like a plain `String` — recovering a JSON
+/// *document* that fails to parse because of an embedded lone UTF-16
+/// surrogate escape (as opposed to raw invalid bytes) is a separate,
+/// whole-document concern handled by [`LossyJson::parse`] before
+/// `LossyString` fields are ever reached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LossyString(pub String);
+
+impl LossyString {
+    /// Reads a non-null C string, replacing any invalid UTF-8 byte sequence
+    /// with U+FFFD rather than panicking or erroring.
+    ///
+    /// # Safety
+    /// `ptr` must be a valid, non-null, null-terminated C string for the
+    /// duration of this call.
+    pub unsafe fn from_c_str(ptr: *const std::os::raw::c_char) -> Self {
+        LossyString(std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+}
+
+impl std::ops::Deref for LossyString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<LossyString> for String {
+    fn from(value: LossyString) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for LossyString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// High-level Rust interface for Phase 2 FFI functions.
 /// Provides safe wrappers around the raw FFI calls with proper memory management.
 pub mod ffi_interface {
-    use std::ffi::{CStr, CString, c_void};
+    use std::ffi::{CStr, c_void};
     use std::ptr;
+    use std::sync::Arc;
     use super::*;
-    use crate::ffi;
+    use crate::ffi_backend::{CSharpBackend, FfiBackend};
+
+    /// Decodes a non-null C string according to the process-wide
+    /// [`Utf8DecodeMode`] toggle (see [`set_ffi_utf8_mode`]).
+    pub(crate) unsafe fn decode_c_str(ptr: *const std::os::raw::c_char) -> Result<String, String> {
+        let bytes = CStr::from_ptr(ptr).to_bytes();
+        match ffi_utf8_mode() {
+            Utf8DecodeMode::Strict => std::str::from_utf8(bytes)
+                .map(|s| s.to_string())
+                .map_err(|e| format!("Invalid UTF-8 from C#: {}", e)),
+            Utf8DecodeMode::Lossy => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
 
     /// Handle to a context managed by the C# side via FFI.
     /// Automatically destroys the context when dropped.
+    ///
+    /// The actual FFI calls go through an injectable [`FfiBackend`], so
+    /// tests can exercise filtering/condition logic against a
+    /// [`MockBackend`](crate::ffi_backend::MockBackend) without a loaded C#
+    /// host — see [`ContextHandle::with_backend`].
     pub struct ContextHandle {
         handle: *mut c_void,
+        /// Local copy of the context properties, kept in sync with the C#
+        /// side so conditions can be evaluated natively without a round-trip.
+        properties: HashMap<String, serde_json::Value>,
+        backend: Arc<dyn FfiBackend>,
     }
 
     impl ContextHandle {
-        /// Creates a new context handle from a plugin configuration.
+        /// Creates a new context handle from a plugin configuration, backed
+        /// by the real [`CSharpBackend`].
         pub fn new(config: &PluginConfiguration) -> Result<Self, String> {
-            let json = config.to_json()
-                .map_err(|e| format!("Failed to serialize config: {}", e))?;
-            let c_json = CString::new(json)
-                .map_err(|e| format!("Failed to create CString: {}", e))?;
-            
-            let handle = unsafe { ffi::create_context_handle(c_json.as_ptr()) };
-            if handle.is_null() {
-                Err("Failed to create context handle".to_string())
-            } else {
-                Ok(ContextHandle { handle })
-            }
+            Self::with_backend(config, Arc::new(CSharpBackend))
+        }
+
+        /// Creates a new context handle backed by an arbitrary [`FfiBackend`],
+        /// e.g. a `MockBackend` in tests.
+        pub fn with_backend(config: &PluginConfiguration, backend: Arc<dyn FfiBackend>) -> Result<Self, String> {
+            let handle = backend.create_context_handle(config)?;
+            Ok(ContextHandle { handle, properties: config.properties.clone(), backend })
         }
 
         /// Updates the context with a new configuration.
         pub fn update(&mut self, config: &PluginConfiguration) -> Result<(), String> {
-            let json = config.to_json()
-                .map_err(|e| format!("Failed to serialize config: {}", e))?;
-            let c_json = CString::new(json)
-                .map_err(|e| format!("Failed to create CString: {}", e))?;
-            
-            let success = unsafe { ffi::update_context_handle(self.handle, c_json.as_ptr()) };
-            if success {
-                Ok(())
-            } else {
-                Err("Failed to update context handle".to_string())
-            }
+            self.backend.update_context_handle(self.handle, config)?;
+            self.properties = config.properties.clone();
+            Ok(())
         }
 
         /// Evaluates a precompiled condition for a specific plugin function.
+        ///
+        /// Conditions the native [`condition`](crate::condition) engine can
+        /// parse (`provider == "Tavily" && maxResults > 5`-style expressions)
+        /// are compiled once, cached, and evaluated entirely in-process
+        /// against this context's properties. Conditions it can't parse fall
+        /// back to the backend's evaluator.
         pub fn evaluate_condition(&self, plugin_type: &str, function_name: &str) -> Result<bool, String> {
-            let c_plugin_type = CString::new(plugin_type)
-                .map_err(|e| format!("Failed to create CString for plugin type: {}", e))?;
-            let c_function_name = CString::new(function_name)
-                .map_err(|e| format!("Failed to create CString for function name: {}", e))?;
-
-            let result = unsafe { 
-                ffi::evaluate_precompiled_condition(
-                    c_plugin_type.as_ptr(), 
-                    c_function_name.as_ptr(), 
-                    self.handle
-                )
-            };
-            Ok(result)
+            if let Some(condition) = crate::condition::condition_source_for(plugin_type, function_name) {
+                if let Some(ast) = crate::condition::compile_condition(plugin_type, function_name, &condition) {
+                    let context = PluginContext { properties: self.properties.clone() };
+                    return Ok(crate::condition::evaluate(&ast, &context));
+                }
+            }
+
+            Ok(self.backend.evaluate_precompiled_condition(plugin_type, function_name, self.handle))
         }
 
         /// Gets available functions for a plugin given this context.
         pub fn get_available_functions(&self, plugin_type: &str) -> Result<Vec<DynamicFunctionMetadata>, String> {
-            let c_plugin_type = CString::new(plugin_type)
-                .map_err(|e| format!("Failed to create CString for plugin type: {}", e))?;
-
-            let result_ptr = unsafe { ffi::filter_available_functions(c_plugin_type.as_ptr(), self.handle) };
-            if result_ptr.is_null() {
-                return Err("FFI function returned null".to_string());
+            let mut metadata = self.backend.filter_available_functions(plugin_type, self.handle)?;
+
+            // Re-derive `is_available` from the native condition engine where
+            // possible, so filtering doesn't need another FFI round-trip once
+            // the condition has been compiled once.
+            for function in &mut metadata {
+                if let Some(condition) = crate::condition::condition_source_for(plugin_type, &function.name) {
+                    if let Some(ast) = crate::condition::compile_condition(plugin_type, &function.name, &condition) {
+                        let context = PluginContext { properties: self.properties.clone() };
+                        function.is_available = crate::condition::evaluate(&ast, &context);
+                    }
+                }
             }
 
-            // Convert the returned JSON to Rust types
-            let json_str = unsafe {
-                let c_str = CStr::from_ptr(result_ptr);
-                c_str.to_str().map_err(|e| format!("Invalid UTF-8 from C#: {}", e))?
-            };
-
-            let metadata: Vec<DynamicFunctionMetadata> = serde_json::from_str(json_str)
-                .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-            // Free the string allocated by C#
-            unsafe { ffi::free_string(result_ptr as *mut c_void) };
-
             Ok(metadata)
         }
 
@@ -243,7 +455,7 @@ pub mod ffi_interface {
     impl Drop for ContextHandle {
         fn drop(&mut self) {
             if !self.handle.is_null() {
-                unsafe { ffi::destroy_context_handle(self.handle) };
+                self.backend.destroy_context_handle(self.handle);
                 self.handle = ptr::null_mut();
             }
         }
@@ -251,23 +463,13 @@ pub mod ffi_interface {
 
     /// Gets metadata for all registered plugins from C#.
     pub fn get_plugin_metadata() -> Result<serde_json::Value, String> {
-        let result_ptr = unsafe { ffi::get_plugin_metadata_json() };
-        if result_ptr.is_null() {
-            return Err("FFI function returned null".to_string());
-        }
-
-        let json_str = unsafe {
-            let c_str = CStr::from_ptr(result_ptr);
-            c_str.to_str().map_err(|e| format!("Invalid UTF-8 from C#: {}", e))?
-        };
-
-        let metadata: serde_json::Value = serde_json::from_str(json_str)
-            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-        // Free the string allocated by C#
-        unsafe { ffi::free_string(result_ptr as *mut c_void) };
+        get_plugin_metadata_with_backend(&CSharpBackend)
+    }
 
-        Ok(metadata)
+    /// Gets plugin metadata through an arbitrary [`FfiBackend`], e.g. a
+    /// `MockBackend` preloaded with [`MockBackend::with_metadata`] in tests.
+    pub fn get_plugin_metadata_with_backend(backend: &dyn FfiBackend) -> Result<serde_json::Value, String> {
+        backend.get_plugin_metadata()
     }
 
     // Thread-safe implementation
@@ -320,4 +522,136 @@ mod tests {
         assert_eq!(config.context_type, deserialized.context_type);
         assert_eq!(config.properties, deserialized.properties);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_toml_round_trip() {
+        let config = PluginConfiguration::new("WebSearchPlugin", "WebSearchPluginMetadataContext")
+            .with_property("provider", "Tavily").unwrap()
+            .with_property("maxResults", 10).unwrap();
+
+        let toml_str = config.to_toml().unwrap();
+        let deserialized = PluginConfiguration::from_toml(&toml_str).unwrap();
+
+        assert_eq!(config.plugin_name, deserialized.plugin_name);
+        assert_eq!(config.properties, deserialized.properties);
+    }
+
+    #[test]
+    fn test_manifest_merges_environment_overrides() {
+        let manifest = r#"
+            [base]
+            pluginName = "WebSearchPlugin"
+            contextType = "WebSearchPluginMetadataContext"
+
+            [base.properties]
+            provider = "Tavily"
+            maxResults = 5
+
+            [env.prod.properties]
+            provider = "Bing"
+            maxResults = 20
+
+            [env.dev.properties]
+            maxResults = 2
+        "#;
+
+        let path = std::env::temp_dir().join("hpd_agent_test_manifest.toml");
+        std::fs::write(&path, manifest).unwrap();
+
+        let prod = PluginConfiguration::from_manifest(path.to_str().unwrap(), "prod").unwrap();
+        assert_eq!(prod.properties["provider"], serde_json::json!("Bing"));
+        assert_eq!(prod.properties["maxResults"], serde_json::json!(20));
+
+        let dev = PluginConfiguration::from_manifest(path.to_str().unwrap(), "dev").unwrap();
+        assert_eq!(dev.properties["provider"], serde_json::json!("Tavily"));
+        assert_eq!(dev.properties["maxResults"], serde_json::json!(2));
+
+        let staging = PluginConfiguration::from_manifest(path.to_str().unwrap(), "staging").unwrap();
+        assert_eq!(staging.properties["provider"], serde_json::json!("Tavily"));
+        assert_eq!(staging.properties["maxResults"], serde_json::json!(5));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_lossy_json_recovers_lone_surrogate() {
+        // `\ud800` alone is a lone high surrogate with no matching low
+        // surrogate, which `serde_json` rejects outright.
+        let json = r#"{"name": "search", "resolvedDescription": "bad \ud800 surrogate", "schema": {}, "isAvailable": true, "requiresPermission": false}"#;
+
+        assert!(serde_json::from_str::<DynamicFunctionMetadata>(json).is_err());
+
+        let recovered: DynamicFunctionMetadata = LossyJson::parse(json).unwrap();
+        assert!(recovered.resolved_description.contains('\u{FFFD}'));
+        assert_eq!(recovered.name, "search");
+    }
+
+    #[test]
+    fn test_lossy_json_leaves_valid_surrogate_pairs_intact() {
+        // A valid surrogate pair (here encoding an emoji) must round-trip
+        // unchanged rather than being treated as lone surrogates. The lone
+        // `\ud800` in `bad` makes strict parsing fail, so recovery runs.
+        let json = concat!(r#"{"ok": "before \ud83d"#, r#"\ude00 after", "bad": "\ud800"}"#);
+        let value: serde_json::Value = LossyJson::parse(json).unwrap();
+        assert_eq!(value["ok"], serde_json::json!("before 😀 after"));
+    }
+
+    #[test]
+    fn test_lossy_string_recovers_invalid_utf8_from_c_str() {
+        let bytes = [b'h', b'i', 0xFF, b'!', 0];
+        let c_string = unsafe { std::ffi::CStr::from_ptr(bytes.as_ptr() as *const std::os::raw::c_char) };
+        assert!(c_string.to_str().is_err());
+
+        let recovered = unsafe { LossyString::from_c_str(bytes.as_ptr() as *const std::os::raw::c_char) };
+        assert_eq!(&*recovered, "hi\u{FFFD}!");
+    }
+
+    #[test]
+    fn test_context_handle_with_mock_backend_filters_by_condition() {
+        use crate::ffi_backend::MockBackend;
+        use std::sync::Arc;
+
+        crate::condition::register_condition_source("WebSearchPlugin", "search_images", "maxResults > 5");
+
+        let config = PluginConfiguration::new("WebSearchPlugin", "WebSearchPluginMetadataContext")
+            .with_property("maxResults", 10).unwrap()
+            .with_available_functions(vec!["search".to_string(), "search_images".to_string()]);
+
+        let handle = ffi_interface::ContextHandle::with_backend(&config, Arc::new(MockBackend::new())).unwrap();
+
+        assert!(handle.evaluate_condition("WebSearchPlugin", "search_images").unwrap());
+
+        let functions = handle.get_available_functions("WebSearchPlugin").unwrap();
+        assert_eq!(functions.len(), 2);
+        assert!(functions.iter().all(|f| f.is_available));
+    }
+
+    #[test]
+    fn test_context_handle_update_invalidates_condition_cache() {
+        use crate::ffi_backend::MockBackend;
+        use std::sync::Arc;
+
+        crate::condition::register_condition_source("WebSearchPlugin", "search_images", "maxResults > 5");
+
+        let low_results = PluginConfiguration::new("WebSearchPlugin", "WebSearchPluginMetadataContext")
+            .with_property("maxResults", 1).unwrap();
+
+        let mut handle = ffi_interface::ContextHandle::with_backend(&low_results, Arc::new(MockBackend::new())).unwrap();
+        assert!(!handle.evaluate_condition("WebSearchPlugin", "search_images").unwrap());
+
+        let high_results = PluginConfiguration::new("WebSearchPlugin", "WebSearchPluginMetadataContext")
+            .with_property("maxResults", 10).unwrap();
+        handle.update(&high_results).unwrap();
+
+        assert!(handle.evaluate_condition("WebSearchPlugin", "search_images").unwrap());
+    }
+
+    #[test]
+    fn test_get_plugin_metadata_with_mock_backend_returns_preloaded_value() {
+        use crate::ffi_backend::MockBackend;
+
+        let backend = MockBackend::new().with_metadata(serde_json::json!({ "plugins": ["MathPlugin"] }));
+        let metadata = ffi_interface::get_plugin_metadata_with_backend(&backend).unwrap();
+
+        assert_eq!(metadata, serde_json::json!({ "plugins": ["MathPlugin"] }));
+    }
\ No newline at end of file
@@ -0,0 +1,377 @@
+//! Pollable streaming execution for plugin functions whose results arrive
+//! incrementally (progress updates, partial output) instead of as one final
+//! string, complementing the single-shot dispatch in
+//! [`crate::plugins::execute_function_async`] and the batch dispatch in
+//! [`crate::plugins::execute_functions_batch`].
+//!
+//! A function opts into streaming by registering a
+//! [`StreamingFunctionExecutor`] with [`register_streaming_executor`]
+//! instead of (or alongside) a regular
+//! [`AsyncFunctionExecutor`](crate::plugins::register_async_executor): it
+//! receives a [`StreamSink`] it can push partial chunks into as it runs.
+//! [`execute_function_streaming`] spawns that executor in the background and
+//! hands back a [`StreamHandle`], which the caller drains with the
+//! non-blocking [`StreamHandle::poll_next`] — no thread is blocked per
+//! in-flight call, and a C# or native event loop can interleave polling with
+//! other work instead of waiting on a single call to finish.
+//!
+//! On Unix, [`StreamHandle`] also exposes a raw `eventfd` via `AsRawFd` that
+//! becomes readable whenever a chunk is available, so it can be registered
+//! directly with a `select`/`epoll`/`kqueue`-based loop alongside other file
+//! descriptors; there's no Windows equivalent here yet (that would need a
+//! waitable `HANDLE`, which this checkout has no existing precedent for).
+//!
+//! The handle owns a background task and, on Unix, an OS file descriptor;
+//! like [`ContextHandle`](crate::plugin_context::ffi_interface::ContextHandle),
+//! both are released deterministically by [`Drop`] rather than requiring the
+//! caller to remember a separate teardown call.
+
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::future::Future;
+use std::os::raw::c_char;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// A chunk of output from a streaming function call.
+#[derive(Debug, Clone)]
+pub enum Chunk {
+    /// A partial result pushed by the executor while it's still running.
+    Data(String),
+    /// The executor finished successfully; no more chunks will follow.
+    Done,
+    /// The executor returned an error; no more chunks will follow.
+    Error(String),
+}
+
+struct StreamSinkInner {
+    tx: mpsc::UnboundedSender<Chunk>,
+    #[cfg(unix)]
+    ready_fd: RawFd,
+}
+
+/// Handed to a streaming executor so it can push partial chunks as it runs.
+/// Cheap to clone and hold onto for the lifetime of the call.
+#[derive(Clone)]
+pub struct StreamSink(Arc<StreamSinkInner>);
+
+impl StreamSink {
+    /// Pushes a partial chunk. Silently dropped if the caller already closed
+    /// the stream (e.g. via [`rust_stream_close`]).
+    pub fn push(&self, chunk: impl Into<String>) {
+        self.send(Chunk::Data(chunk.into()));
+    }
+
+    fn send(&self, chunk: Chunk) {
+        let _ = self.0.tx.send(chunk);
+        #[cfg(unix)]
+        signal_eventfd(self.0.ready_fd);
+    }
+}
+
+/// A streaming executor: given the call's arguments JSON and a [`StreamSink`]
+/// to push partial results into, it resolves once the call is fully done.
+type StreamingFunctionExecutor = Box<dyn Fn(String, StreamSink) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync>;
+
+static STREAMING_EXECUTORS: Lazy<Mutex<HashMap<String, StreamingFunctionExecutor>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Registers a streaming executor for `name`, in the separate registry
+/// [`execute_function_streaming`] draws from (independent of the one-shot
+/// registry behind [`crate::plugins::register_async_executor`] — a function
+/// may be registered in either, or both, depending on which dispatch mode it
+/// supports).
+pub fn register_streaming_executor(name: String, executor: StreamingFunctionExecutor) {
+    if let Ok(mut registry) = STREAMING_EXECUTORS.lock() {
+        registry.insert(name, executor);
+    }
+}
+
+/// A handle to a function call running in the background. Poll it with
+/// [`poll_next`](StreamHandle::poll_next) until it yields [`Chunk::Done`] or
+/// [`Chunk::Error`]; dropping it early aborts the call.
+pub struct StreamHandle {
+    receiver: mpsc::UnboundedReceiver<Chunk>,
+    task: Option<JoinHandle<()>>,
+    finished: bool,
+    #[cfg(unix)]
+    ready_fd: RawFd,
+}
+
+impl StreamHandle {
+    /// Returns the next available chunk, or `None` if none is ready yet —
+    /// distinct from completion, callers should poll again later. Once a
+    /// [`Chunk::Done`] or [`Chunk::Error`] has been returned, every
+    /// subsequent call returns `None`.
+    pub fn poll_next(&mut self) -> Option<Chunk> {
+        if self.finished {
+            return None;
+        }
+
+        match self.receiver.try_recv() {
+            Ok(chunk) => {
+                if matches!(chunk, Chunk::Done | Chunk::Error(_)) {
+                    self.finished = true;
+                    self.task = None;
+                }
+                #[cfg(unix)]
+                drain_eventfd(self.ready_fd);
+                Some(chunk)
+            }
+            Err(_) => None,
+        }
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        #[cfg(unix)]
+        if self.ready_fd >= 0 {
+            unsafe { libc::close(self.ready_fd) };
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for StreamHandle {
+    /// An `eventfd` that becomes readable whenever a chunk is queued. Reads
+    /// drain it back to non-readable; the handle closes it on drop.
+    fn as_raw_fd(&self) -> RawFd {
+        self.ready_fd
+    }
+}
+
+#[cfg(unix)]
+fn make_eventfd() -> RawFd {
+    unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) }
+}
+
+#[cfg(unix)]
+fn signal_eventfd(fd: RawFd) {
+    if fd >= 0 {
+        let one: u64 = 1;
+        unsafe { libc::write(fd, &one as *const u64 as *const c_void, std::mem::size_of::<u64>()) };
+    }
+}
+
+#[cfg(unix)]
+fn drain_eventfd(fd: RawFd) {
+    if fd >= 0 {
+        let mut value: u64 = 0;
+        unsafe { libc::read(fd, &mut value as *mut u64 as *mut c_void, std::mem::size_of::<u64>()) };
+    }
+}
+
+/// Starts a registered streaming function call in the background and
+/// returns a handle to poll its chunks. Fails immediately (without spawning
+/// anything) if no streaming executor is registered for `name`.
+pub fn execute_function_streaming(name: &str, args_json: &str) -> Result<StreamHandle, String> {
+    {
+        let registry = STREAMING_EXECUTORS.lock().map_err(|_| "Streaming executor registry lock poisoned".to_string())?;
+        if !registry.contains_key(name) {
+            return Err(format!("No streaming executor registered for '{}'", name));
+        }
+    }
+
+    let (tx, rx) = mpsc::unbounded_channel::<Chunk>();
+    #[cfg(unix)]
+    let ready_fd = make_eventfd();
+    let sink = StreamSink(Arc::new(StreamSinkInner {
+        tx,
+        #[cfg(unix)]
+        ready_fd,
+    }));
+
+    let name_owned = name.to_string();
+    let args_owned = args_json.to_string();
+    let sink_for_exec = sink.clone();
+
+    let task = tokio::spawn(async move {
+        let future = {
+            let registry = match STREAMING_EXECUTORS.lock() {
+                Ok(registry) => registry,
+                Err(_) => {
+                    sink.send(Chunk::Error("Streaming executor registry lock poisoned".to_string()));
+                    return;
+                }
+            };
+
+            match registry.get(&name_owned) {
+                Some(exec) => exec(args_owned, sink_for_exec),
+                None => {
+                    sink.send(Chunk::Error(format!("No streaming executor registered for '{}'", name_owned)));
+                    return;
+                }
+            }
+        };
+
+        let result = future.await;
+        sink.send(match result {
+            Ok(()) => Chunk::Done,
+            Err(e) => Chunk::Error(e),
+        });
+    });
+
+    Ok(StreamHandle {
+        receiver: rx,
+        task: Some(task),
+        finished: false,
+        #[cfg(unix)]
+        ready_fd,
+    })
+}
+
+/// Active streams exposed across the FFI boundary, keyed by the id encoded
+/// into the opaque handle `rust_stream_plugin_function` returns.
+static ACTIVE_STREAMS: Lazy<Mutex<HashMap<usize, StreamHandle>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_STREAM_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+/// FFI entry point for [`execute_function_streaming`].
+///
+/// Returns an opaque handle for use with [`rust_stream_poll`] and
+/// [`rust_stream_close`], or a null pointer if `name`/`args_json` aren't
+/// valid or no streaming executor is registered for `name`.
+///
+/// # Safety
+/// `name_ptr` and `args_ptr` must be valid, null-terminated C strings for
+/// the duration of this call.
+#[no_mangle]
+pub unsafe extern "C" fn rust_stream_plugin_function(name_ptr: *const c_char, args_ptr: *const c_char) -> *mut c_void {
+    if name_ptr.is_null() || args_ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let name = CStr::from_ptr(name_ptr).to_string_lossy().into_owned();
+    let args_json = CStr::from_ptr(args_ptr).to_string_lossy().into_owned();
+
+    match execute_function_streaming(&name, &args_json) {
+        Ok(handle) => {
+            let id = NEXT_STREAM_HANDLE.fetch_add(1, Ordering::SeqCst);
+            if let Ok(mut streams) = ACTIVE_STREAMS.lock() {
+                streams.insert(id, handle);
+            }
+            id as *mut c_void
+        }
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// FFI entry point for [`StreamHandle::poll_next`].
+///
+/// Returns a newly allocated null-terminated JSON string describing the next
+/// chunk (`{"type":"data","value":...}`, `{"type":"done"}`, or
+/// `{"type":"error","message":...}`), or a null pointer if no chunk is ready
+/// yet or `handle` is unknown. A non-null return value must be released with
+/// [`rust_stream_free_string`].
+///
+/// # Safety
+/// `handle` must be a value previously returned by
+/// [`rust_stream_plugin_function`] that hasn't been passed to
+/// [`rust_stream_close`].
+#[no_mangle]
+pub unsafe extern "C" fn rust_stream_poll(handle: *mut c_void) -> *mut c_char {
+    let id = handle as usize;
+
+    let chunk = {
+        let mut streams = match ACTIVE_STREAMS.lock() {
+            Ok(streams) => streams,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        match streams.get_mut(&id) {
+            Some(stream) => stream.poll_next(),
+            None => return std::ptr::null_mut(),
+        }
+    };
+
+    match chunk {
+        None => std::ptr::null_mut(),
+        Some(Chunk::Data(value)) => to_c_string(serde_json::json!({ "type": "data", "value": value })),
+        Some(Chunk::Done) => to_c_string(serde_json::json!({ "type": "done" })),
+        Some(Chunk::Error(message)) => to_c_string(serde_json::json!({ "type": "error", "message": message })),
+    }
+}
+
+fn to_c_string(value: serde_json::Value) -> *mut c_char {
+    CString::new(value.to_string()).unwrap_or_default().into_raw()
+}
+
+/// FFI entry point that releases a handle returned by
+/// [`rust_stream_plugin_function`], aborting its background task and (on
+/// Unix) closing its readiness `eventfd` via [`StreamHandle`]'s `Drop`.
+///
+/// # Safety
+/// `handle` must be a value previously returned by
+/// [`rust_stream_plugin_function`] that hasn't already been closed.
+#[no_mangle]
+pub unsafe extern "C" fn rust_stream_close(handle: *mut c_void) {
+    let id = handle as usize;
+    if let Ok(mut streams) = ACTIVE_STREAMS.lock() {
+        streams.remove(&id);
+    }
+}
+
+/// Releases a string returned by [`rust_stream_poll`].
+///
+/// # Safety
+/// `ptr` must have been returned by [`rust_stream_poll`] and not already
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn rust_stream_free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn register_counting_executor(name: &str, steps: u32) {
+        register_streaming_executor(name.to_string(), Box::new(move |_args, sink| {
+            Box::pin(async move {
+                for i in 0..steps {
+                    sink.push(format!("step {}", i));
+                }
+                Ok(())
+            })
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_function_streaming_yields_chunks_then_done() {
+        register_counting_executor("test_stream_counter", 3);
+
+        let mut handle = execute_function_streaming("test_stream_counter", "{}").unwrap();
+
+        let mut data_chunks = Vec::new();
+        loop {
+            match handle.poll_next() {
+                Some(Chunk::Data(value)) => data_chunks.push(value),
+                Some(Chunk::Done) => break,
+                Some(Chunk::Error(e)) => panic!("unexpected error chunk: {}", e),
+                None => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+
+        assert_eq!(data_chunks, vec!["step 0", "step 1", "step 2"]);
+        assert!(handle.poll_next().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_function_streaming_unknown_function_errors_immediately() {
+        let result = execute_function_streaming("no_such_streaming_function", "{}");
+        assert!(result.is_err());
+    }
+}
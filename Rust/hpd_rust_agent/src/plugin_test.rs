@@ -0,0 +1,95 @@
+//! In-process plugin test harness.
+//!
+//! The hand-written assertions in [`crate::tests::test_module4`] don't
+//! round-trip a function call through [`execute_function_async`] against its
+//! declared schema. This module does exactly that: given a plugin name, it
+//! runs each of the plugin's functions that has attached [`FunctionExample`]s,
+//! calling it through the real executor and schema-generation path, and
+//! reports a human-readable diff on mismatch.
+
+use crate::plugins::{execute_function_async, get_plugin, FunctionExample};
+
+/// The outcome of running a single example invocation.
+#[derive(Debug)]
+pub struct ExampleResult {
+    pub function_name: String,
+    pub args: serde_json::Value,
+    pub expected: serde_json::Value,
+    pub actual: Result<serde_json::Value, String>,
+}
+
+impl ExampleResult {
+    pub fn passed(&self) -> bool {
+        matches!(&self.actual, Ok(actual) if actual == &self.expected)
+    }
+
+    /// A human-readable description of the mismatch, or `None` if the
+    /// example passed.
+    pub fn diff(&self) -> Option<String> {
+        if self.passed() {
+            return None;
+        }
+
+        Some(match &self.actual {
+            Ok(actual) => format!(
+                "{}({}) => expected {}, got {}",
+                self.function_name, self.args, self.expected, actual
+            ),
+            Err(e) => format!(
+                "{}({}) => expected {}, but call failed: {}",
+                self.function_name, self.args, self.expected, e
+            ),
+        })
+    }
+}
+
+/// Runs every attached example for `plugin_name`'s functions through
+/// [`execute_function_async`] and returns one [`ExampleResult`] per example.
+pub async fn run_plugin_examples(plugin_name: &str) -> Result<Vec<ExampleResult>, String> {
+    let plugin = get_plugin(plugin_name)
+        .ok_or_else(|| format!("No plugin registered with name '{}'", plugin_name))?;
+
+    let mut results = Vec::new();
+    for (function_name, examples) in &plugin.examples {
+        for example in examples {
+            results.push(run_example(function_name, example).await);
+        }
+    }
+
+    Ok(results)
+}
+
+async fn run_example(function_name: &str, example: &FunctionExample) -> ExampleResult {
+    let actual = execute_function_async(function_name, &example.args.to_string())
+        .await
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| format!("Invalid JSON returned: {}", e)));
+
+    ExampleResult {
+        function_name: function_name.to_string(),
+        args: example.args.clone(),
+        expected: example.expected.clone(),
+        actual,
+    }
+}
+
+/// Asserts that every example attached to `plugin_name`'s functions passes.
+///
+/// Panics with a readable diff of every failing example, so this can be
+/// dropped into a `#[tokio::test]` as a single call instead of bespoke
+/// per-function asserts.
+pub async fn assert_plugin_examples(plugin_name: &str) {
+    let results = run_plugin_examples(plugin_name)
+        .await
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    let failures: Vec<String> = results.iter().filter_map(ExampleResult::diff).collect();
+
+    assert!(
+        failures.is_empty(),
+        "plugin '{}' failed {} of {} example(s):\n{}",
+        plugin_name,
+        failures.len(),
+        results.len(),
+        failures.join("\n")
+    );
+}
@@ -1,15 +1,36 @@
 use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use serde_json::Value as JsonValue;
 use once_cell::sync::Lazy;
 use std::pin::Pin;
 use std::future::Future;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
 type AsyncFunctionExecutor = Box<dyn Fn(String) -> Pin<Box<dyn Future<Output = Result<String, String>> + Send>> + Send + Sync>;
 
-static FUNCTION_EXECUTORS: Lazy<Mutex<HashMap<String, AsyncFunctionExecutor>>> = 
+static FUNCTION_EXECUTORS: Lazy<Mutex<HashMap<String, AsyncFunctionExecutor>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Per-function call counts, reported by [`get_plugin_stats`]. Incremented
+/// once per call to [`execute_function_async`], regardless of whether it's
+/// reached directly or fanned out through [`execute_functions_batch`].
+static CALL_COUNTS: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Total number of calls that have run through [`execute_functions_batch`]'s
+/// concurrent dispatch path (as opposed to a lone [`execute_function_async`]
+/// call), reported by [`get_plugin_stats`].
+static TOTAL_CONCURRENT_EXECUTIONS: AtomicU64 = AtomicU64::new(0);
+
+fn record_call(name: &str) {
+    if let Ok(mut counts) = CALL_COUNTS.lock() {
+        *counts.entry(name.to_string()).or_insert(0) += 1;
+    }
+}
+
 /// Register an async function executor
 pub fn register_async_executor(name: String, executor: AsyncFunctionExecutor) {
     if let Ok(mut registry) = FUNCTION_EXECUTORS.lock() {
@@ -18,8 +39,52 @@ pub fn register_async_executor(name: String, executor: AsyncFunctionExecutor) {
     }
 }
 
-/// Execute a registered function asynchronously
+/// Execute a registered function asynchronously.
+///
+/// `args_json` is sanitized before being handed to the executor: a C# caller
+/// can produce a lone UTF-16 surrogate in a string field (its internal
+/// representation is UTF-16), which every executor's own `serde_json::from_str`
+/// would otherwise reject outright. Valid JSON is passed through unchanged;
+/// only a document that fails to parse is retried with lone surrogates
+/// replaced by U+FFFD, so one malformed argument string doesn't hard-fail
+/// the call. This doesn't cover the raw `CStr` decoding [`rust_execute_plugin_function`]
+/// does before `args_json` ever reaches here; that entry point decodes with
+/// `to_string_lossy`, replacing invalid UTF-8 with U+FFFD the same way
+/// [`crate::plugin_context::LossyString`] does elsewhere.
+///
+/// If `name` has a registered schema (see [`get_schema_for`]), the arguments
+/// are also validated and coerced against it via
+/// [`crate::argument_validation::validate_and_coerce_args`] before dispatch,
+/// so a model's near-miss call (a numeric string where an integer was
+/// expected, say) either gets fixed up or fails with a specific `Err`
+/// instead of a confusing failure inside the function body.
 pub async fn execute_function_async(name: &str, args_json: &str) -> Result<String, String> {
+    record_call(name);
+
+    if let Some(owner) = find_owning_plugin(name) {
+        match plugin_state(&owner) {
+            Some(PluginState::Finished) | Some(PluginState::Cleaned) | None => {}
+            Some(state) => {
+                return Err(format!(
+                    "Function '{}' belongs to plugin '{}' which is not ready yet (state: {:?}); call finalize_plugins() first",
+                    name, owner, state
+                ));
+            }
+        }
+    }
+
+    let args_json = sanitize_args_json(args_json);
+    let args_json = match get_schema_for(name) {
+        Some(schema) => {
+            let args: JsonValue = serde_json::from_str(&args_json)
+                .map_err(|e| format!("Invalid arguments JSON for '{}': {}", name, e))?;
+            let coerced = crate::argument_validation::validate_and_coerce_args(&schema, &args)?;
+            serde_json::to_string(&coerced)
+                .map_err(|e| format!("Failed to re-serialize arguments for '{}': {}", name, e))?
+        }
+        None => args_json,
+    };
+
     let executor = {
         let registry = FUNCTION_EXECUTORS.lock()
             .map_err(|_| "Failed to lock function executor registry".to_string())?;
@@ -40,7 +105,7 @@ pub async fn execute_function_async(name: &str, args_json: &str) -> Result<Strin
             .map_err(|_| "Failed to lock function executor registry".to_string())?;
         
         if let Some(exec) = registry.get(name) {
-            exec(args_json.to_string()).await
+            exec(args_json).await
         } else {
             Err(format!("Function '{}' not found in executor registry", name))
         }
@@ -49,6 +114,340 @@ pub async fn execute_function_async(name: &str, args_json: &str) -> Result<Strin
     }
 }
 
+/// Returns `args_json` unchanged if it parses as JSON; otherwise replaces
+/// any lone UTF-16 surrogate escape with U+FFFD and retries, so a document
+/// an executor would otherwise fail to parse becomes a degraded-but-valid one.
+fn sanitize_args_json(args_json: &str) -> String {
+    if serde_json::from_str::<JsonValue>(args_json).is_ok() {
+        args_json.to_string()
+    } else {
+        crate::plugin_context::sanitize_lone_surrogates(args_json)
+    }
+}
+
+/// Executes several registered functions concurrently, bounded to at most
+/// `num_cpus::get()` calls in flight at once.
+///
+/// Each `(name, args_json)` call in `calls` is dispatched to
+/// [`execute_function_async`] on its own `tokio` task gated by a shared
+/// [`Semaphore`], so independent tool calls from the same LLM turn run in
+/// parallel instead of one-at-a-time, but a model requesting an unbounded
+/// number of calls in one turn can't spawn unbounded concurrent work.
+/// Results are returned in the same order as `calls` regardless of which
+/// task finishes first. A call that panics doesn't take the rest of the
+/// batch down with it - and neither does a call that simply fails: both
+/// become that call's own `Err`, never an `Err` for the whole batch.
+pub async fn execute_functions_batch(calls: Vec<(String, String)>) -> Vec<Result<String, String>> {
+    let permits = std::sync::Arc::new(Semaphore::new(num_cpus::get()));
+
+    let mut join_set = JoinSet::new();
+    for (index, (name, args_json)) in calls.into_iter().enumerate() {
+        let permits = std::sync::Arc::clone(&permits);
+        join_set.spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("semaphore is never closed");
+            (index, execute_function_async(&name, &args_json).await)
+        });
+    }
+
+    let total = join_set.len();
+    TOTAL_CONCURRENT_EXECUTIONS.fetch_add(total as u64, Ordering::Relaxed);
+
+    let mut results: Vec<Option<Result<String, String>>> = (0..total).map(|_| None).collect();
+    while let Some(outcome) = join_set.join_next().await {
+        match outcome {
+            Ok((index, result)) => results[index] = Some(result),
+            Err(join_err) => {
+                // We don't know which index panicked once `JoinSet` has lost
+                // track of it, but every slot still gets an `Err` instead of
+                // silently staying `None`, so the caller never sees a result
+                // vector shorter than its call list.
+                if let Some(slot) = results.iter_mut().find(|slot| slot.is_none()) {
+                    *slot = Some(Err(format!("Function call panicked: {}", join_err)));
+                }
+            }
+        }
+    }
+
+    results.into_iter().map(|result| result.unwrap_or_else(|| Err("Function call did not complete".to_string()))).collect()
+}
+
+/// A single entry in the JSON array `rust_execute_plugin_functions_batch`
+/// accepts: a function name plus its arguments as a JSON value, serialized
+/// to a string before dispatch.
+#[derive(serde::Deserialize)]
+struct BatchCallRequest {
+    name: String,
+    args: JsonValue,
+}
+
+/// Runtime used to bridge the synchronous FFI entry points below onto the
+/// async [`execute_functions_batch`]. The rest of this crate's async work
+/// runs on whatever runtime the host binary (e.g. `hpd_console_app`) already
+/// drives with `#[tokio::main]`; this one exists only because C# calls
+/// across the FFI boundary are synchronous and have no runtime of their own
+/// to hand us.
+static BATCH_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
+    tokio::runtime::Runtime::new().expect("Failed to start batch-dispatch runtime")
+});
+
+/// FFI entry point for [`execute_functions_batch`].
+///
+/// `calls_json_ptr` must point to a null-terminated UTF-8 JSON array of
+/// `{"name": ..., "args": ...}` objects. Returns a newly allocated
+/// null-terminated JSON array of per-call results, each either
+/// `{"ok": <result>}` or `{"error": <message>}`, in the same order as the
+/// input. The returned pointer is owned by Rust and must be released with
+/// [`rust_free_batch_result_string`] — mirroring the ownership split
+/// `ContextHandle`/`free_string` use for C#-owned strings, just in the
+/// opposite direction.
+///
+/// # Safety
+/// `calls_json_ptr` must be a valid, null-terminated C string for the
+/// duration of this call.
+#[no_mangle]
+pub unsafe extern "C" fn rust_execute_plugin_functions_batch(calls_json_ptr: *const c_char) -> *mut c_char {
+    if calls_json_ptr.is_null() {
+        return CString::new(r#"{"error":"calls_json_ptr was null"}"#).unwrap().into_raw();
+    }
+
+    let calls_json = CStr::from_ptr(calls_json_ptr).to_string_lossy().into_owned();
+
+    let requests: Vec<BatchCallRequest> = match serde_json::from_str(&calls_json) {
+        Ok(requests) => requests,
+        Err(e) => {
+            let error = serde_json::json!({ "error": format!("Invalid calls_json: {}", e) });
+            return CString::new(error.to_string()).unwrap_or_default().into_raw();
+        }
+    };
+
+    let calls: Vec<(String, String)> = requests.into_iter()
+        .map(|request| (request.name, request.args.to_string()))
+        .collect();
+
+    let results = BATCH_RUNTIME.block_on(execute_functions_batch(calls));
+
+    let response: Vec<JsonValue> = results.into_iter()
+        .map(|result| match result {
+            Ok(value) => serde_json::json!({ "ok": value }),
+            Err(error) => serde_json::json!({ "error": error }),
+        })
+        .collect();
+
+    CString::new(serde_json::Value::Array(response).to_string())
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// Releases a string returned by [`rust_execute_plugin_functions_batch`],
+/// [`rust_execute_plugin_function`] or [`rust_approve_pending_call`].
+///
+/// # Safety
+/// `ptr` must have been returned by one of those functions and not already
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn rust_free_batch_result_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+/// Decision returned by an approval policy installed with
+/// [`crate::agent::AgentBuilder::with_approval_policy`] for a gated
+/// (`may_`- or `execute_`-prefixed) function call reaching [`rust_execute_plugin_function`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Approval {
+    /// Run the call immediately.
+    Allow,
+    /// Refuse the call outright.
+    Deny,
+    /// Park the call rather than running or refusing it yet, to be resolved
+    /// later by a human through [`rust_approve_pending_call`].
+    AskUser,
+}
+
+type ApprovalPolicyFn = dyn Fn(&str, &JsonValue) -> Approval + Send + Sync;
+
+/// The process-wide approval policy consulted for gated calls. `None` means
+/// no policy has been installed, in which case gated calls are allowed by
+/// default (preserving the pre-existing auto-invoke behavior for callers
+/// that don't opt into approval gating).
+static APPROVAL_POLICY: Lazy<Mutex<Option<Box<ApprovalPolicyFn>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Installs the process-wide approval policy. Called from
+/// [`crate::agent::AgentBuilder::with_approval_policy`].
+pub fn set_approval_policy(policy: impl Fn(&str, &JsonValue) -> Approval + Send + Sync + 'static) {
+    if let Ok(mut slot) = APPROVAL_POLICY.lock() {
+        *slot = Some(Box::new(policy));
+    }
+}
+
+/// Whether `name` is a side-effecting function that must be approved before
+/// it runs, per the `may_`/`execute_`-prefix convention (see
+/// [`crate::conversation::is_side_effecting`]).
+fn is_gated(name: &str) -> bool {
+    crate::conversation::is_side_effecting(name)
+}
+
+/// Resolves the [`Approval`] decision for a call to `name`. Non-gated calls
+/// are always [`Approval::Allow`]; gated calls consult [`APPROVAL_POLICY`],
+/// defaulting to `Allow` if none is installed.
+fn approval_for(name: &str, args: &JsonValue) -> Approval {
+    if !is_gated(name) {
+        return Approval::Allow;
+    }
+
+    APPROVAL_POLICY.lock().ok()
+        .and_then(|policy| policy.as_ref().map(|policy| policy(name, args)))
+        .unwrap_or(Approval::Allow)
+}
+
+/// A gated call parked by an [`Approval::AskUser`] decision, waiting to be
+/// resumed by [`rust_approve_pending_call`].
+struct PendingCall {
+    name: String,
+    args_json: String,
+}
+
+static PENDING_CALLS: Lazy<Mutex<HashMap<String, PendingCall>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_CALL_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+fn next_call_id() -> String {
+    let id = NEXT_CALL_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    format!("call_{}", id)
+}
+
+/// The outcome of dispatching one call through [`dispatch_gated`]: either it
+/// ran (successfully or not), or it's parked awaiting a human decision.
+pub(crate) enum GatedOutcome {
+    Ran(Result<String, String>),
+    Pending { call_id: String },
+}
+
+/// Renders a [`GatedOutcome`] as the `{"success": ..., ...}` JSON shape
+/// [`rust_execute_plugin_function`] and [`rust_approve_pending_call`] hand
+/// back to C#.
+fn gated_outcome_to_json(outcome: &GatedOutcome) -> JsonValue {
+    match outcome {
+        GatedOutcome::Ran(Ok(value)) => serde_json::json!({ "success": true, "result": value }),
+        GatedOutcome::Ran(Err(error)) => serde_json::json!({ "success": false, "error": error }),
+        GatedOutcome::Pending { call_id } => serde_json::json!({ "success": false, "pending_approval": true, "call_id": call_id }),
+    }
+}
+
+/// Dispatches one gated call: consults [`approval_for`], then either runs it
+/// through [`execute_function_async`], refuses it, or parks it for later
+/// approval via [`rust_approve_pending_call`]. Used by both the single-call
+/// FFI entry point below and [`crate::agent::Agent::run_until_complete`]'s
+/// Rust-native orchestration loop.
+pub(crate) async fn dispatch_gated(name: &str, args_json: &str) -> GatedOutcome {
+    let args: JsonValue = serde_json::from_str(args_json).unwrap_or(JsonValue::Null);
+
+    match approval_for(name, &args) {
+        Approval::Deny => GatedOutcome::Ran(Err(format!("Call to '{}' was denied by the approval policy", name))),
+        Approval::AskUser => {
+            let call_id = next_call_id();
+            if let Ok(mut pending) = PENDING_CALLS.lock() {
+                pending.insert(call_id.clone(), PendingCall { name: name.to_string(), args_json: args_json.to_string() });
+            }
+            GatedOutcome::Pending { call_id }
+        }
+        Approval::Allow => GatedOutcome::Ran(execute_function_async(name, args_json).await),
+    }
+}
+
+/// FFI entry point for a single plugin-function call, gated by the approval
+/// policy installed through [`crate::agent::AgentBuilder::with_approval_policy`].
+///
+/// A `may_`- or `execute_`-prefixed call the policy answers with `AskUser`
+/// returns `{"success": false, "pending_approval": true, "call_id": "..."}`
+/// instead of running; resume it later with [`rust_approve_pending_call`].
+/// One it answers with `Deny` returns `{"success": false, "error": ...}`. Every
+/// other call runs immediately through [`execute_function_async`], same as
+/// [`rust_execute_plugin_functions_batch`] does for a batch.
+///
+/// Returns a newly allocated null-terminated string, encoded with the
+/// [`WireEncoding`](crate::wire::WireEncoding) `agent_handle` negotiated at
+/// agent creation (see [`crate::agent::AgentBuilder::with_encoding`]); the
+/// caller owns it and must release it with [`rust_free_batch_result_string`].
+///
+/// Also emits an OpenTelemetry child span tagged with the function name,
+/// success, and duration (see [`crate::telemetry`]), a no-op unless the
+/// crate is built with the `otel` feature.
+///
+/// # Safety
+/// `name_ptr` and `args_json_ptr` must be valid, null-terminated C strings
+/// for the duration of this call. `args_json_ptr` holds the negotiated
+/// encoding's representation of the arguments, not necessarily raw JSON.
+/// `agent_handle` must be the handle the C# host received back from
+/// [`FfiBackend::create_agent`](crate::ffi_backend::FfiBackend::create_agent)
+/// for the agent this call belongs to, so the right encoding is looked up -
+/// passing a handle for a different agent decodes/encodes with that other
+/// agent's encoding instead.
+#[no_mangle]
+pub unsafe extern "C" fn rust_execute_plugin_function(
+    agent_handle: *mut c_void,
+    name_ptr: *const c_char,
+    args_json_ptr: *const c_char,
+) -> *mut c_char {
+    if name_ptr.is_null() || args_json_ptr.is_null() {
+        return CString::new(r#"{"success":false,"error":"name_ptr or args_json_ptr was null"}"#).unwrap().into_raw();
+    }
+
+    let name = CStr::from_ptr(name_ptr).to_string_lossy().into_owned();
+    let args_payload = CStr::from_ptr(args_json_ptr).to_string_lossy().into_owned();
+    let encoding = crate::wire::encoding_for_handle(agent_handle);
+    let span = crate::telemetry::start_function_span(&name);
+
+    let outcome = match crate::wire::decode::<JsonValue>(&args_payload, encoding) {
+        Ok(args) => BATCH_RUNTIME.block_on(dispatch_gated(&name, &args.to_string())),
+        Err(error) => GatedOutcome::Ran(Err(format!("Failed to decode arguments: {}", error))),
+    };
+
+    let success = matches!(outcome, GatedOutcome::Ran(Ok(_)) | GatedOutcome::Pending { .. });
+    span.finish(success);
+
+    let response = gated_outcome_to_json(&outcome);
+    let encoded = crate::wire::encode(&response, encoding).unwrap_or_else(|_| response.to_string());
+    CString::new(encoded).unwrap_or_default().into_raw()
+}
+
+/// Resumes a call parked by [`rust_execute_plugin_function`]'s `AskUser`
+/// decision, running it unconditionally since a human has already decided.
+/// Returns the same `{"success": ..., "result"/"error": ...}` shape, or
+/// `{"success": false, "error": ...}` if `call_id` isn't pending (already
+/// resolved, or never existed).
+///
+/// Returns a newly allocated null-terminated string, encoded with the
+/// [`WireEncoding`](crate::wire::WireEncoding) `agent_handle` negotiated at
+/// agent creation, same as [`rust_execute_plugin_function`]; the caller owns
+/// it and must release it with [`rust_free_batch_result_string`].
+///
+/// # Safety
+/// `call_id_ptr` must be a valid, null-terminated C string for the duration
+/// of this call. `agent_handle` must be the handle for the agent whose call
+/// is being resumed, same caveat as [`rust_execute_plugin_function`].
+#[no_mangle]
+pub unsafe extern "C" fn rust_approve_pending_call(agent_handle: *mut c_void, call_id_ptr: *const c_char) -> *mut c_char {
+    if call_id_ptr.is_null() {
+        return CString::new(r#"{"success":false,"error":"call_id_ptr was null"}"#).unwrap().into_raw();
+    }
+
+    let call_id = CStr::from_ptr(call_id_ptr).to_string_lossy().into_owned();
+    let pending = PENDING_CALLS.lock().ok().and_then(|mut pending| pending.remove(&call_id));
+
+    let response = match pending {
+        Some(call) => {
+            let outcome = BATCH_RUNTIME.block_on(async { GatedOutcome::Ran(execute_function_async(&call.name, &call.args_json).await) });
+            gated_outcome_to_json(&outcome)
+        }
+        None => serde_json::json!({ "success": false, "error": format!("No pending call with id '{}'", call_id) }),
+    };
+
+    let encoding = crate::wire::encoding_for_handle(agent_handle);
+    let encoded = crate::wire::encode(&response, encoding).unwrap_or_else(|_| response.to_string());
+    CString::new(encoded).unwrap_or_default().into_raw()
+}
+
 /// Plugin registration information
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PluginRegistration {
@@ -56,19 +455,95 @@ pub struct PluginRegistration {
     pub description: String,
     pub functions: Vec<(String, String)>, // (function_name, wrapper_function_name)
     pub schemas: HashMap<String, String>,
+    /// Whether this plugin may only be registered once. Defaults to `true`,
+    /// mirroring Bevy's `Plugin::is_unique()` — set to `false` for plugins
+    /// that are intentionally registered multiple times (e.g. one instance
+    /// per connection). Duplicate registrations of a unique plugin are
+    /// skipped rather than replacing the existing one.
+    #[serde(default = "default_is_unique")]
+    pub is_unique: bool,
+    /// Example invocations for functions in this plugin, keyed by function
+    /// name, used by [`crate::plugin_test`] to round-trip calls through
+    /// [`execute_function_async`] against the declared schema.
+    #[serde(default)]
+    pub examples: HashMap<String, Vec<FunctionExample>>,
+}
+
+/// A single example invocation of a plugin function: the arguments to pass
+/// and the output expected back from [`execute_function_async`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FunctionExample {
+    pub args: JsonValue,
+    pub expected: JsonValue,
+}
+
+fn default_is_unique() -> bool {
+    true
 }
 
 /// Global plugin registry
 static PLUGIN_REGISTRY: Mutex<Vec<PluginRegistration>> = Mutex::new(Vec::new());
 
-/// Register a plugin with the global registry
+/// Register a plugin with the global registry.
+///
+/// If a plugin with the same `name` is already registered and either the
+/// existing or the incoming registration is unique, the new registration is
+/// skipped (logged, not an error) to avoid doubling up functions and stats
+/// in [`get_plugin_stats`].
 pub fn register_plugin(plugin: PluginRegistration) {
     if let Ok(mut registry) = PLUGIN_REGISTRY.lock() {
+        if registry.iter().any(|p| p.name == plugin.name && (plugin.is_unique || p.is_unique)) {
+            println!("Skipped duplicate registration of unique plugin: {}", plugin.name);
+            return;
+        }
+
+        let name = plugin.name.clone();
         registry.push(plugin);
-        println!("Registered plugin: {}", registry.last().unwrap().name);
+        println!("Registered plugin: {}", name);
     }
 }
 
+/// Removes `name`'s registration from the registry along with every executor
+/// registered for its functions, undoing [`register_plugin`]. Used before
+/// unloading a plugin's backing library/module (see
+/// [`crate::dynamic_plugins::unload_plugin_library`]), since a leftover
+/// executor closure would otherwise call into memory that's about to be
+/// unmapped.
+pub fn unregister_plugin(name: &str) {
+    let removed_functions = match PLUGIN_REGISTRY.lock() {
+        Ok(mut registry) => {
+            let mut removed_functions = Vec::new();
+            registry.retain(|plugin| {
+                if plugin.name == name {
+                    removed_functions.extend(plugin.functions.iter().map(|(function_name, _)| function_name.clone()));
+                    false
+                } else {
+                    true
+                }
+            });
+            removed_functions
+        }
+        Err(_) => return,
+    };
+
+    if let Ok(mut executors) = FUNCTION_EXECUTORS.lock() {
+        for function_name in &removed_functions {
+            executors.remove(function_name);
+        }
+    }
+}
+
+/// Checks whether a plugin with the given name has already been registered.
+///
+/// Unlike comparing instances, this is name-based so it can be called from
+/// within a plugin's own lifecycle callbacks to conditionally register
+/// functions only when a dependency plugin is present.
+pub fn is_plugin_added(name: &str) -> bool {
+    PLUGIN_REGISTRY.lock()
+        .map(|registry| registry.iter().any(|p| p.name == name))
+        .unwrap_or(false)
+}
+
 /// Get all registered plugins
 pub fn get_registered_plugins() -> Vec<PluginRegistration> {
     PLUGIN_REGISTRY.lock().unwrap_or_else(|_| {
@@ -82,22 +557,55 @@ pub fn get_plugin(name: &str) -> Option<PluginRegistration> {
     PLUGIN_REGISTRY.lock().ok()?.iter().find(|p| p.name == name).cloned()
 }
 
+/// Merges the schema-visible bounds [`schema_constraints`](crate::schema_constraints)
+/// documents for the handful of built-in example functions whose `#[ai_function]`
+/// attribute can't carry `min`/`max`/`pattern` in this tree (the proc-macro crate
+/// that would parse those attributes isn't vendored here - see the module doc on
+/// [`schema_constraints`](crate::schema_constraints)). No-op for every other
+/// function name.
+fn apply_known_example_constraints(func_name: &str, schema: &mut JsonValue) {
+    use crate::schema_constraints::{merge_constraints, ParamConstraints};
+
+    match func_name {
+        "factorial" => {
+            merge_constraints(schema, "n", &ParamConstraints::new().with_range(0.0, 20.0));
+        }
+        "network_request" => {
+            merge_constraints(schema, "url", &ParamConstraints::new().with_pattern("^https://"));
+        }
+        _ => {}
+    }
+}
+
 /// Get all function schemas as a single JSON object
 pub fn get_all_schemas() -> JsonValue {
     let plugins = get_registered_plugins();
     let mut all_schemas = serde_json::Map::new();
-    
+
     for plugin in plugins {
         for (func_name, schema_str) in plugin.schemas {
-            if let Ok(schema) = serde_json::from_str::<JsonValue>(&schema_str) {
+            if let Ok(mut schema) = serde_json::from_str::<JsonValue>(&schema_str) {
+                apply_known_example_constraints(&func_name, &mut schema);
                 all_schemas.insert(func_name.to_string(), schema);
             }
         }
     }
-    
+
     JsonValue::Object(all_schemas)
 }
 
+/// Gets the parsed JSON Schema for a single registered function, if any.
+/// Used by [`execute_function_async`] to validate and coerce arguments
+/// before dispatch.
+pub fn get_schema_for(name: &str) -> Option<JsonValue> {
+    let plugins = get_registered_plugins();
+    let mut schema = plugins.iter()
+        .find_map(|plugin| plugin.schemas.get(name))
+        .and_then(|schema_str| serde_json::from_str::<JsonValue>(schema_str).ok())?;
+    apply_known_example_constraints(name, &mut schema);
+    Some(schema)
+}
+
 /// List all available function names
 pub fn list_functions() -> Vec<String> {
     let plugins = get_registered_plugins();
@@ -112,19 +620,222 @@ pub fn list_functions() -> Vec<String> {
     functions
 }
 
-/// Get plugin statistics
+/// Get plugin statistics, including per-function call counts and the total
+/// number of calls dispatched through [`execute_functions_batch`]'s
+/// concurrent path, both accumulated since process start.
 pub fn get_plugin_stats() -> JsonValue {
     let plugins = get_registered_plugins();
     let total_plugins = plugins.len();
     let total_functions: usize = plugins.iter().map(|p| p.functions.len()).sum();
-    
+    let call_counts = CALL_COUNTS.lock().map(|counts| counts.clone()).unwrap_or_default();
+
     serde_json::json!({
         "total_plugins": total_plugins,
         "total_functions": total_functions,
         "plugins": plugins.iter().map(|p| serde_json::json!({
             "name": p.name,
             "description": p.description,
-            "function_count": p.functions.len()
-        })).collect::<Vec<_>>()
+            "function_count": p.functions.len(),
+            "call_count": p.functions.iter()
+                .map(|(func_name, _)| call_counts.get(func_name).copied().unwrap_or(0))
+                .sum::<u64>(),
+        })).collect::<Vec<_>>(),
+        "call_counts": call_counts,
+        "total_concurrent_executions": TOTAL_CONCURRENT_EXECUTIONS.load(Ordering::Relaxed),
     })
 }
+
+/// Finds which registered plugin owns a given function name, if any.
+fn find_owning_plugin(function_name: &str) -> Option<String> {
+    PLUGIN_REGISTRY.lock().ok()?.iter()
+        .find(|p| p.functions.iter().any(|(name, _)| name == function_name))
+        .map(|p| p.name.clone())
+}
+
+/// The stage of a plugin's lifecycle, modeled on Bevy's `PluginsState`.
+///
+/// A plugin moves through these states in order: it starts `Adding` as soon as
+/// it's registered, becomes `Ready` once its `ready()` callback reports true,
+/// `Finished` after `finish()` has run (at which point its functions become
+/// callable through [`execute_function_async`]), and finally `Cleaned` after
+/// `cleanup()` has run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginState {
+    Adding,
+    Ready,
+    Finished,
+    Cleaned,
+}
+
+/// Optional staged-setup callbacks for a plugin that needs to acquire an
+/// async resource (GPU device, network handle, loaded model) before its
+/// functions can be called.
+///
+/// Plugins that don't need staged setup can keep using [`register_plugin`]
+/// directly; they're treated as immediately `Ready` with no-op `finish`/
+/// `cleanup` steps.
+#[derive(Default)]
+pub struct PluginLifecycle {
+    /// Run once, synchronously, when the plugin is registered.
+    pub build: Option<Box<dyn FnOnce() + Send>>,
+    /// Polled repeatedly by [`finalize_plugins`] until it returns `true`.
+    pub ready: Option<Box<dyn Fn() -> bool + Send + Sync>>,
+    /// Run once all plugins report ready; functions become callable after this.
+    pub finish: Option<Box<dyn FnOnce() + Send>>,
+    /// Run once after `finish`, for teardown of any build-time resources.
+    pub cleanup: Option<Box<dyn FnOnce() + Send>>,
+}
+
+struct PluginLifecycleEntry {
+    state: PluginState,
+    lifecycle: PluginLifecycle,
+}
+
+/// Per-plugin lifecycle state, keyed by plugin name. Plugins registered via
+/// [`register_plugin`] get a default entry that is trivially `Ready`.
+static PLUGIN_LIFECYCLE: Mutex<Vec<(String, PluginLifecycleEntry)>> = Mutex::new(Vec::new());
+
+/// Registers a plugin along with staged lifecycle callbacks.
+///
+/// `lifecycle.build` (if present) runs immediately. The plugin then sits in
+/// [`PluginState::Adding`] until [`finalize_plugins`] polls its `ready()`
+/// callback to completion.
+pub fn register_plugin_with_lifecycle(plugin: PluginRegistration, mut lifecycle: PluginLifecycle) {
+    if let Some(build) = lifecycle.build.take() {
+        build();
+    }
+
+    let name = plugin.name.clone();
+    register_plugin(plugin);
+
+    if let Ok(mut states) = PLUGIN_LIFECYCLE.lock() {
+        states.push((name, PluginLifecycleEntry { state: PluginState::Adding, lifecycle }));
+    }
+}
+
+/// Returns the current lifecycle state of a plugin, or `None` if it was
+/// registered without one (such plugins are implicitly ready).
+pub fn plugin_state(name: &str) -> Option<PluginState> {
+    PLUGIN_LIFECYCLE.lock().ok()?.iter()
+        .find(|(plugin_name, _)| plugin_name == name)
+        .map(|(_, entry)| entry.state)
+}
+
+/// Drives every registered plugin through its remaining lifecycle phases.
+///
+/// Polls each plugin's `ready()` callback until every plugin reports `true`
+/// (allowing async resource acquisition to complete in the background), then
+/// runs `finish()` on each plugin, then `cleanup()` on each plugin. Plugins
+/// registered without a lifecycle are left untouched; their functions are
+/// already callable.
+pub fn finalize_plugins() {
+    loop {
+        let all_ready = {
+            let mut states = match PLUGIN_LIFECYCLE.lock() {
+                Ok(states) => states,
+                Err(_) => return,
+            };
+
+            let mut all_ready = true;
+            for (_, entry) in states.iter_mut() {
+                if entry.state != PluginState::Adding {
+                    continue;
+                }
+                let ready = match &entry.lifecycle.ready {
+                    Some(ready_fn) => ready_fn(),
+                    None => true,
+                };
+                if ready {
+                    entry.state = PluginState::Ready;
+                } else {
+                    all_ready = false;
+                }
+            }
+            all_ready
+        };
+
+        if all_ready {
+            break;
+        }
+        std::thread::yield_now();
+    }
+
+    if let Ok(mut states) = PLUGIN_LIFECYCLE.lock() {
+        for (name, entry) in states.iter_mut() {
+            if entry.state != PluginState::Ready {
+                continue;
+            }
+            if let Some(finish) = entry.lifecycle.finish.take() {
+                finish();
+            }
+            entry.state = PluginState::Finished;
+            println!("Plugin '{}' finished initialization", name);
+        }
+
+        for (_, entry) in states.iter_mut() {
+            if entry.state != PluginState::Finished {
+                continue;
+            }
+            if let Some(cleanup) = entry.lifecycle.cleanup.take() {
+                cleanup();
+            }
+            entry.state = PluginState::Cleaned;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plugin(name: &str, is_unique: bool) -> PluginRegistration {
+        PluginRegistration {
+            name: name.to_string(),
+            description: "test plugin".to_string(),
+            functions: Vec::new(),
+            schemas: HashMap::new(),
+            is_unique,
+            examples: HashMap::new(),
+        }
+    }
+
+    /// `is_unique: false` on the *incoming* registration alone shouldn't be
+    /// enough to bypass the dup check when the already-registered entry with
+    /// the same name is itself unique - the doc comment says "either" is
+    /// enough to skip, not just the incoming side.
+    #[test]
+    fn test_register_plugin_skips_when_existing_registration_is_unique() {
+        let name = "test_register_plugin_skips_when_existing_registration_is_unique_plugin";
+        register_plugin(test_plugin(name, true));
+        register_plugin(test_plugin(name, false));
+
+        let count = PLUGIN_REGISTRY.lock().unwrap().iter().filter(|p| p.name == name).count();
+        assert_eq!(count, 1, "a non-unique re-registration should still be skipped when the existing entry is unique");
+    }
+
+    #[test]
+    fn test_register_plugin_allows_multiple_when_neither_is_unique() {
+        let name = "test_register_plugin_allows_multiple_when_neither_is_unique_plugin";
+        register_plugin(test_plugin(name, false));
+        register_plugin(test_plugin(name, false));
+
+        let count = PLUGIN_REGISTRY.lock().unwrap().iter().filter(|p| p.name == name).count();
+        assert_eq!(count, 2, "two non-unique registrations of the same name should both be kept");
+    }
+
+    #[test]
+    fn test_unregister_plugin_removes_registration_and_executors() {
+        let name = "test_unregister_plugin_removes_registration_and_executors_plugin";
+        let function_name = "test_unregister_plugin_removes_registration_and_executors_function".to_string();
+
+        let mut plugin = test_plugin(name, true);
+        plugin.functions.push((function_name.clone(), "wrapper".to_string()));
+        register_plugin(plugin);
+        register_async_executor(function_name.clone(), Box::new(|args| Box::pin(async move { Ok(args) })));
+
+        unregister_plugin(name);
+
+        assert!(!PLUGIN_REGISTRY.lock().unwrap().iter().any(|p| p.name == name));
+        assert!(!FUNCTION_EXECUTORS.lock().unwrap().contains_key(&function_name));
+    }
+}
@@ -0,0 +1,283 @@
+//! Scriptable plugins defined in an embedded Rhai engine.
+//!
+//! Every other plugin backend ([`example_plugins`](crate::example_plugins),
+//! [`dynamic_plugins`](crate::dynamic_plugins), [`wasm_plugins`](crate::wasm_plugins))
+//! requires compiling Rust (or WASM) code. This one lets a user drop a
+//! `.rhai` script next to the agent and have its functions appear as AI
+//! functions without rebuilding anything.
+//!
+//! ## Script contract
+//!
+//! A script defines one function per AI tool, plus a single `__hpd_metadata`
+//! function that returns an array of maps describing them:
+//!
+//! ```text
+//! fn add(a, b) {
+//!     a + b
+//! }
+//!
+//! fn __hpd_metadata() {
+//!     [
+//!         #{
+//!             name: "add",
+//!             description: "Add two numbers together",
+//!             params: [ #{ name: "a", type: "number" }, #{ name: "b", type: "number" } ],
+//!         },
+//!     ]
+//! }
+//! ```
+//!
+//! `type` is one of `"number"`, `"integer"`, `"string"`, `"boolean"` and maps
+//! to the matching JSON Schema type, matching the schema shape
+//! [`crate::plugins::get_all_schemas`] emits for compiled plugins. A param
+//! map may also carry any of `min`/`max`, `max_len`, `pattern`, or `enum`,
+//! which are merged into the generated schema via
+//! [`crate::schema_constraints::merge_constraints`]:
+//!
+//! ```text
+//! #{ name: "n", type: "integer", min: 0, max: 20 }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde_json::Value as JsonValue;
+
+use crate::plugins::{register_async_executor, register_plugin, PluginRegistration};
+use crate::schema_constraints::{merge_constraints, ParamConstraints};
+
+/// Resource bounds applied to every script loaded through this module.
+#[derive(Debug, Clone, Copy)]
+pub struct RhaiPluginLimits {
+    /// Maximum number of Rhai operations a single function call may perform
+    /// before it's aborted.
+    pub max_operations: u64,
+    /// Maximum call stack depth.
+    pub max_call_levels: usize,
+}
+
+impl Default for RhaiPluginLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 500_000,
+            max_call_levels: 32,
+        }
+    }
+}
+
+struct FunctionMetadata {
+    name: String,
+    description: String,
+    params: Vec<(String, String)>, // (param name, JSON schema type)
+    constraints: HashMap<String, ParamConstraints>,
+}
+
+/// Loads a Rhai script as a plugin, named `plugin_name`, and registers one
+/// [`AsyncFunctionExecutor`](crate::plugins) per function described by the
+/// script's `__hpd_metadata` function.
+pub fn load_rhai_plugin(plugin_name: &str, script_path: &str, limits: RhaiPluginLimits) -> Result<(), String> {
+    let source = fs::read_to_string(script_path)
+        .map_err(|e| format!("Failed to read Rhai script '{}': {}", script_path, e))?;
+
+    let engine = new_engine(limits);
+    let ast = engine.compile(&source)
+        .map_err(|e| format!("Failed to compile Rhai script '{}': {}", script_path, e))?;
+
+    let metadata = read_metadata(&engine, &ast)?;
+
+    let mut functions = Vec::new();
+    let mut schemas = HashMap::new();
+
+    for function in &metadata {
+        functions.push((function.name.clone(), function.name.clone()));
+        schemas.insert(function.name.clone(), function_schema(function).to_string());
+
+        let function_name = function.name.clone();
+        let param_names: Vec<String> = function.params.iter().map(|(name, _)| name.clone()).collect();
+        let ast = ast.clone();
+        let limits = limits;
+
+        register_async_executor(
+            function.name.clone(),
+            Box::new(move |args_json| {
+                let function_name = function_name.clone();
+                let param_names = param_names.clone();
+                let ast = ast.clone();
+                Box::pin(async move { call_script_function(&ast, &function_name, &param_names, &args_json, limits) })
+            }),
+        );
+    }
+
+    register_plugin(PluginRegistration {
+        name: plugin_name.to_string(),
+        description: format!("Rhai script plugin loaded from {}", script_path),
+        functions,
+        schemas,
+        is_unique: true,
+        examples: HashMap::new(),
+    });
+
+    Ok(())
+}
+
+fn new_engine(limits: RhaiPluginLimits) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(limits.max_operations);
+    engine.set_max_call_levels(limits.max_call_levels);
+    engine.set_max_expr_depths(64, 32);
+    // Scripts only compute values; they get no access to the filesystem,
+    // process spawning, or other host-affecting builtins.
+    engine.disable_symbol("eval");
+    engine
+}
+
+fn read_metadata(engine: &Engine, ast: &AST) -> Result<Vec<FunctionMetadata>, String> {
+    let metadata: Dynamic = engine.call_fn(&mut Scope::new(), ast, "__hpd_metadata", ())
+        .map_err(|e| format!("Script is missing a valid __hpd_metadata() function: {}", e))?;
+
+    let entries = metadata.into_typed_array::<rhai::Map>()
+        .map_err(|e| format!("__hpd_metadata() must return an array of maps: {}", e))?;
+
+    entries.into_iter().map(|entry| {
+        let name = entry.get("name")
+            .and_then(|v| v.clone().into_string().ok())
+            .ok_or_else(|| "Function metadata missing string 'name'".to_string())?;
+        let description = entry.get("description")
+            .and_then(|v| v.clone().into_string().ok())
+            .unwrap_or_default();
+
+        let param_maps = entry.get("params")
+            .cloned()
+            .unwrap_or_else(|| Dynamic::from(rhai::Array::new()))
+            .into_typed_array::<rhai::Map>()
+            .map_err(|e| format!("Function '{}' has an invalid 'params' list: {}", name, e))?;
+
+        let mut params = Vec::new();
+        let mut constraints = HashMap::new();
+
+        for param in param_maps {
+            let param_name = param.get("name").and_then(|v| v.clone().into_string().ok()).unwrap_or_default();
+            let param_type = param.get("type").and_then(|v| v.clone().into_string().ok()).unwrap_or_else(|| "string".to_string());
+
+            let param_constraints = param_constraints_from_map(&param);
+            if !param_name.is_empty() {
+                constraints.insert(param_name.clone(), param_constraints);
+            }
+
+            params.push((param_name, param_type));
+        }
+
+        Ok(FunctionMetadata { name, description, params, constraints })
+    }).collect()
+}
+
+/// Reads `min`/`max`, `max_len`, `pattern`, and `enum` keys off a Rhai
+/// param map into a [`ParamConstraints`], if present.
+fn param_constraints_from_map(param: &rhai::Map) -> ParamConstraints {
+    let mut constraints = ParamConstraints::new();
+
+    let min = param.get("min").and_then(|v| v.as_float().ok().or_else(|| v.as_int().ok().map(|i| i as f64)));
+    let max = param.get("max").and_then(|v| v.as_float().ok().or_else(|| v.as_int().ok().map(|i| i as f64)));
+    if let (Some(min), Some(max)) = (min, max) {
+        constraints = constraints.with_range(min, max);
+    }
+
+    if let Some(max_len) = param.get("max_len").and_then(|v| v.as_int().ok()) {
+        constraints = constraints.with_max_len(max_len.max(0) as u64);
+    }
+
+    if let Some(pattern) = param.get("pattern").and_then(|v| v.clone().into_string().ok()) {
+        constraints = constraints.with_pattern(pattern);
+    }
+
+    if let Some(values) = param.get("enum").cloned().and_then(|v| v.into_typed_array::<Dynamic>().ok()) {
+        constraints = constraints.with_enum(values.iter().map(dynamic_to_json).collect());
+    }
+
+    constraints
+}
+
+/// Builds the same OpenAI-style function schema shape that
+/// [`crate::plugins::get_all_schemas`] assembles for compiled plugins, then
+/// merges in each parameter's [`ParamConstraints`].
+fn function_schema(function: &FunctionMetadata) -> JsonValue {
+    let properties: serde_json::Map<String, JsonValue> = function.params.iter()
+        .map(|(name, ty)| (name.clone(), serde_json::json!({ "type": ty })))
+        .collect();
+
+    let required: Vec<&String> = function.params.iter().map(|(name, _)| name).collect();
+
+    let mut schema = serde_json::json!({
+        "type": "function",
+        "function": {
+            "name": function.name,
+            "description": function.description,
+            "parameters": {
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }
+        }
+    });
+
+    for (param_name, constraints) in &function.constraints {
+        merge_constraints(&mut schema, param_name, constraints);
+    }
+
+    schema
+}
+
+fn call_script_function(ast: &AST, function_name: &str, param_names: &[String], args_json: &str, limits: RhaiPluginLimits) -> Result<String, String> {
+    let args: JsonValue = serde_json::from_str(args_json)
+        .map_err(|e| format!("Invalid args JSON for '{}': {}", function_name, e))?;
+
+    let engine = new_engine(limits);
+    let call_args: Vec<Dynamic> = param_names.iter()
+        .map(|name| json_to_dynamic(args.get(name).unwrap_or(&JsonValue::Null)))
+        .collect();
+
+    let result: Dynamic = engine.call_fn(&mut Scope::new(), ast, function_name, call_args)
+        .map_err(|e| format!("Script function '{}' failed: {}", function_name, e))?;
+
+    serde_json::to_string(&dynamic_to_json(&result))
+        .map_err(|e| format!("Failed to serialize result of '{}': {}", function_name, e))
+}
+
+fn json_to_dynamic(value: &JsonValue) -> Dynamic {
+    match value {
+        JsonValue::Null => Dynamic::UNIT,
+        JsonValue::Bool(b) => Dynamic::from(*b),
+        JsonValue::Number(n) => n.as_i64().map(Dynamic::from)
+            .unwrap_or_else(|| Dynamic::from(n.as_f64().unwrap_or(0.0))),
+        JsonValue::String(s) => Dynamic::from(s.clone()),
+        JsonValue::Array(items) => Dynamic::from(items.iter().map(json_to_dynamic).collect::<rhai::Array>()),
+        JsonValue::Object(map) => {
+            let mut rhai_map = rhai::Map::new();
+            for (k, v) in map {
+                rhai_map.insert(k.into(), json_to_dynamic(v));
+            }
+            Dynamic::from(rhai_map)
+        }
+    }
+}
+
+fn dynamic_to_json(value: &Dynamic) -> JsonValue {
+    if value.is_unit() {
+        JsonValue::Null
+    } else if let Some(b) = value.clone().try_cast::<bool>() {
+        JsonValue::Bool(b)
+    } else if let Some(i) = value.clone().try_cast::<i64>() {
+        JsonValue::from(i)
+    } else if let Some(f) = value.clone().try_cast::<f64>() {
+        serde_json::Number::from_f64(f).map(JsonValue::Number).unwrap_or(JsonValue::Null)
+    } else if let Some(s) = value.clone().try_cast::<rhai::ImmutableString>() {
+        JsonValue::String(s.to_string())
+    } else if let Some(array) = value.clone().try_cast::<rhai::Array>() {
+        JsonValue::Array(array.iter().map(dynamic_to_json).collect())
+    } else if let Some(map) = value.clone().try_cast::<rhai::Map>() {
+        JsonValue::Object(map.iter().map(|(k, v)| (k.to_string(), dynamic_to_json(v))).collect())
+    } else {
+        JsonValue::String(value.to_string())
+    }
+}
@@ -0,0 +1,169 @@
+//! Declarative parameter constraints merged into generated JSON Schemas.
+//!
+//! A schema that describes only a parameter's name and base type gives a
+//! model calling `factorial` or `simulate_network_request` no bounds to
+//! respect, so it finds out it guessed wrong only from a runtime
+//! `Err(String)`. This module
+//! defines [`ParamConstraints`] and [`merge_constraints`], which fold
+//! `minimum`/`maximum`/`maxLength`/`pattern`/`enum` into an existing
+//! parameter schema so those bounds are declarative and visible to the model
+//! up front.
+//!
+//! This is intentionally only the schema-merging half. Actually *validating*
+//! incoming arguments against the merged schema before a function body runs
+//! is a separate concern handled by [`argument_validation`](crate::argument_validation).
+//!
+//! Note: the `#[ai_function]` attribute itself lives in the proc-macro crate
+//! this checkout doesn't vendor, so it can't be extended here to *parse*
+//! `min`/`max`/`pattern`/`enum` out of the attribute and call
+//! [`merge_constraints`] automatically. Instead, the bounds for
+//! `MathPlugin::factorial` (`n` in 0..=20) and
+//! `AsyncPlugin::simulate_network_request` (an https-only `url` pattern) are
+//! merged by function name in `plugins::apply_known_example_constraints`;
+//! hand-built schemas like the Rhai backend's use this module directly.
+
+use serde_json::Value as JsonValue;
+
+/// Constraints for a single function parameter, mirroring the JSON Schema
+/// keywords they map onto.
+#[derive(Debug, Clone, Default)]
+pub struct ParamConstraints {
+    /// Numeric lower bound (`minimum`).
+    pub min: Option<f64>,
+    /// Numeric upper bound (`maximum`).
+    pub max: Option<f64>,
+    /// Maximum string length (`maxLength`).
+    pub max_len: Option<u64>,
+    /// Regex a string value must match (`pattern`).
+    pub pattern: Option<String>,
+    /// Allowed literal values (`enum`).
+    pub enum_values: Option<Vec<JsonValue>>,
+}
+
+impl ParamConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    pub fn with_max_len(mut self, max_len: u64) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn with_enum(mut self, values: Vec<JsonValue>) -> Self {
+        self.enum_values = Some(values);
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none() && self.max_len.is_none()
+            && self.pattern.is_none() && self.enum_values.is_none()
+    }
+}
+
+/// Merges `constraints` into `schema`'s `function.parameters.properties.<param_name>`
+/// object, in place. No-op if that property doesn't exist in `schema` or if
+/// `constraints` is empty.
+pub fn merge_constraints(schema: &mut JsonValue, param_name: &str, constraints: &ParamConstraints) {
+    if constraints.is_empty() {
+        return;
+    }
+
+    let Some(property) = schema
+        .pointer_mut("/function/parameters/properties")
+        .and_then(|properties| properties.get_mut(param_name))
+    else {
+        return;
+    };
+
+    let Some(object) = property.as_object_mut() else { return };
+
+    if let Some(min) = constraints.min {
+        object.insert("minimum".to_string(), serde_json::json!(min));
+    }
+    if let Some(max) = constraints.max {
+        object.insert("maximum".to_string(), serde_json::json!(max));
+    }
+    if let Some(max_len) = constraints.max_len {
+        object.insert("maxLength".to_string(), serde_json::json!(max_len));
+    }
+    if let Some(pattern) = &constraints.pattern {
+        object.insert("pattern".to_string(), serde_json::json!(pattern));
+    }
+    if let Some(values) = &constraints.enum_values {
+        object.insert("enum".to_string(), serde_json::json!(values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_constraints_adds_bounds_to_existing_property() {
+        let mut schema = serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": "factorial",
+                "description": "Calculate factorial",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "n": { "type": "integer" } },
+                    "required": ["n"],
+                }
+            }
+        });
+
+        merge_constraints(&mut schema, "n", &ParamConstraints::new().with_range(0.0, 20.0));
+
+        let property = &schema["function"]["parameters"]["properties"]["n"];
+        assert_eq!(property["minimum"], serde_json::json!(0.0));
+        assert_eq!(property["maximum"], serde_json::json!(20.0));
+        assert_eq!(property["type"], serde_json::json!("integer"));
+    }
+
+    #[test]
+    fn test_merge_constraints_is_noop_for_unknown_property() {
+        let mut schema = serde_json::json!({
+            "function": { "parameters": { "properties": {} } }
+        });
+
+        merge_constraints(&mut schema, "missing", &ParamConstraints::new().with_max_len(10));
+
+        assert_eq!(schema["function"]["parameters"]["properties"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_merge_constraints_pattern_and_enum() {
+        let mut schema = serde_json::json!({
+            "function": {
+                "parameters": {
+                    "properties": { "url": { "type": "string" } }
+                }
+            }
+        });
+
+        merge_constraints(
+            &mut schema,
+            "url",
+            &ParamConstraints::new()
+                .with_pattern("^https://")
+                .with_enum(vec![serde_json::json!("https://a"), serde_json::json!("https://b")]),
+        );
+
+        let property = &schema["function"]["parameters"]["properties"]["url"];
+        assert_eq!(property["pattern"], serde_json::json!("^https://"));
+        assert_eq!(property["enum"][0], serde_json::json!("https://a"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Optional OpenTelemetry tracing and metrics for the crate's hot paths:
+//! [`AgentBuilder::build`](crate::agent::AgentBuilder::build) and every
+//! [`rust_execute_plugin_function`](crate::plugins::rust_execute_plugin_function)
+//! invocation. Gated behind the `otel` cargo feature, same as an optional
+//! plugin backend (`dynamic_plugins`/`wasm_plugins`/`rhai_plugins`) is gated
+//! behind its own loader rather than always being compiled in: with the
+//! feature off, every function here compiles to a no-op and the crate never
+//! even depends on the `opentelemetry` crates.
+//!
+//! [`OtelConfig`] installed via
+//! [`AgentBuilder::with_telemetry`](crate::agent::AgentBuilder::with_telemetry)
+//! initializes an OTLP exporter for traces only. The counters/histograms are
+//! recorded through the global meter provider, for which no exporter is
+//! installed here, and no logs are exported.
+
+/// Where to export spans and metrics, and what service name to tag them
+/// with. Passed to [`AgentBuilder::with_telemetry`](crate::agent::AgentBuilder::with_telemetry).
+#[derive(Debug, Clone)]
+pub struct OtelConfig {
+    pub endpoint: String,
+    pub service_name: String,
+}
+
+#[cfg(feature = "otel")]
+mod enabled {
+    use super::OtelConfig;
+    use once_cell::sync::OnceCell;
+    use opentelemetry::metrics::{Counter, Histogram};
+    use opentelemetry::trace::{Span as OtelSpanTrait, Tracer};
+    use opentelemetry::{global, KeyValue};
+
+    static FUNCTION_CALL_COUNTER: OnceCell<Counter<u64>> = OnceCell::new();
+    static FUNCTION_LATENCY_MS: OnceCell<Histogram<f64>> = OnceCell::new();
+    static FUNCTION_ERROR_COUNTER: OnceCell<Counter<u64>> = OnceCell::new();
+
+    /// Installs an OTLP trace exporter for `config` as the global tracer
+    /// provider, then creates this crate's counters/histograms from the
+    /// global meter provider. No metrics exporter is installed. Calling it
+    /// again re-points the global tracer provider at the new endpoint; the
+    /// counters/histograms created by the first call are kept.
+    pub fn init(config: &OtelConfig) -> Result<(), String> {
+        let exporter = opentelemetry_otlp::new_exporter()
+            .tonic()
+            .with_endpoint(config.endpoint.clone());
+
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(exporter)
+            .with_trace_config(
+                opentelemetry_sdk::trace::config().with_resource(opentelemetry_sdk::Resource::new(vec![
+                    KeyValue::new("service.name", config.service_name.clone()),
+                ])),
+            )
+            .install_batch(opentelemetry_sdk::runtime::Tokio)
+            .map_err(|e| format!("Failed to install OTLP trace pipeline: {}", e))?;
+
+        let meter = global::meter(config.service_name.clone());
+        FUNCTION_CALL_COUNTER
+            .set(meter.u64_counter("plugin.function_calls").init())
+            .ok();
+        FUNCTION_LATENCY_MS
+            .set(meter.f64_histogram("plugin.function_call.duration_ms").init())
+            .ok();
+        FUNCTION_ERROR_COUNTER
+            .set(meter.u64_counter("plugin.function_call.errors").init())
+            .ok();
+
+        Ok(())
+    }
+
+    /// An in-flight span plus the wall-clock start used to derive its
+    /// duration. Call [`SpanGuard::finish`] exactly once, when the operation
+    /// it covers completes.
+    pub struct SpanGuard {
+        span: global::BoxedSpan,
+        function_name: Option<String>,
+        start: std::time::Instant,
+    }
+
+    fn start_span(name: &str, attributes: Vec<KeyValue>) -> SpanGuard {
+        let tracer = global::tracer("hpd_rust_agent");
+        let mut span = tracer.start(name.to_string());
+        for attribute in attributes {
+            span.set_attribute(attribute);
+        }
+        SpanGuard { span, function_name: None, start: std::time::Instant::now() }
+    }
+
+    /// Span around `AgentBuilder::build`, tagged with the provider, model,
+    /// and plugin count being built.
+    pub fn start_build_span(provider: &str, model: &str, plugin_count: usize) -> SpanGuard {
+        start_span(
+            "AgentBuilder::build",
+            vec![
+                KeyValue::new("provider", provider.to_string()),
+                KeyValue::new("model", model.to_string()),
+                KeyValue::new("plugin_count", plugin_count as i64),
+            ],
+        )
+    }
+
+    /// Span around one `rust_execute_plugin_function` invocation, tagged
+    /// with the function name so its latency and error-rate metrics can be
+    /// broken down per function.
+    pub fn start_function_span(function_name: &str) -> SpanGuard {
+        let mut guard = start_span("rust_execute_plugin_function", vec![KeyValue::new("function", function_name.to_string())]);
+        guard.function_name = Some(function_name.to_string());
+        guard
+    }
+
+    impl SpanGuard {
+        /// Ends the span and records the call-count/latency/error metrics,
+        /// tagged `success` and (for function spans) the function name.
+        pub fn finish(mut self, success: bool) {
+            let duration_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+            self.span.set_attribute(KeyValue::new("success", success));
+            self.span.set_attribute(KeyValue::new("duration_ms", duration_ms));
+            self.span.end();
+
+            let attributes: &[KeyValue] = &self
+                .function_name
+                .as_ref()
+                .map(|name| vec![KeyValue::new("function", name.clone())])
+                .unwrap_or_default();
+
+            if let Some(counter) = FUNCTION_CALL_COUNTER.get() {
+                counter.add(1, attributes);
+            }
+            if let Some(histogram) = FUNCTION_LATENCY_MS.get() {
+                histogram.record(duration_ms, attributes);
+            }
+            if !success {
+                if let Some(counter) = FUNCTION_ERROR_COUNTER.get() {
+                    counter.add(1, attributes);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+mod enabled {
+    use super::OtelConfig;
+
+    pub fn init(_config: &OtelConfig) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// No-op stand-in for the `otel`-enabled [`SpanGuard`] when the feature
+    /// is off, so call sites don't need their own `#[cfg]`.
+    pub struct SpanGuard;
+
+    pub fn start_build_span(_provider: &str, _model: &str, _plugin_count: usize) -> SpanGuard {
+        SpanGuard
+    }
+
+    pub fn start_function_span(_function_name: &str) -> SpanGuard {
+        SpanGuard
+    }
+
+    impl SpanGuard {
+        pub fn finish(self, _success: bool) {}
+    }
+}
+
+pub use enabled::{init, start_build_span, start_function_span, SpanGuard};
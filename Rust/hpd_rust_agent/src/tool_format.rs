@@ -0,0 +1,317 @@
+//! Per-provider translation between the crate's generic plugin tool schemas
+//! and each provider's native function-calling wire format.
+//!
+//! [`crate::plugins::get_all_schemas`] returns every registered function's
+//! schema in OpenAI's own `{"type": "function", "function": {...}}` shape,
+//! which OpenRouter's OpenAI-compatible endpoint accepts as-is. Anthropic's
+//! Messages API wants a flat `tools` array of `{"name", "description",
+//! "input_schema"}` entries instead, and reports a call back as a `tool_use`
+//! content block rather than an OpenAI-style `tool_calls` array — sending
+//! either provider what the other expects would simply be rejected.
+//! [`ToolFormat`] abstracts this: each [`ChatProvider`] gets an
+//! implementation that builds its native tools payload from the crate's
+//! generic schemas, parses its native assistant-response shape into a
+//! [`ModelTurn`], and formats a function's result back into whatever content
+//! block that provider expects a tool result to look like — so
+//! [`Conversation`](crate::conversation::Conversation)'s loop stays
+//! provider-agnostic and only [`conversation::CSharpModelBackend`](crate::conversation::CSharpModelBackend)
+//! needs to know which format applies.
+
+use serde_json::Value as JsonValue;
+
+use crate::agent::ChatProvider;
+use crate::conversation::ModelTurn;
+
+/// Translates between the crate's generic plugin schemas / [`ModelTurn`] and
+/// one provider's native function-calling wire format.
+pub trait ToolFormat: Send + Sync {
+    /// Builds the provider-native `tools` (or equivalent) payload from the
+    /// crate's generic OpenAI-shaped function schemas.
+    fn build_tools(&self, schemas: &[JsonValue]) -> JsonValue;
+
+    /// Parses one provider-native assistant message into the crate's
+    /// internal [`ModelTurn`] representation.
+    fn parse_assistant_message(&self, message: &JsonValue) -> ModelTurn;
+
+    /// Formats a tool's result as the provider-native message to append to
+    /// the conversation, keyed by the original call's id.
+    fn format_tool_result(&self, call_id: &str, content: &str, is_error: bool) -> JsonValue;
+
+    /// Formats a recorded assistant turn (its text, if any, plus any calls it
+    /// requested) back into the provider-native message to resend on the
+    /// next round trip. This is the inverse of [`parse_assistant_message`](Self::parse_assistant_message)
+    /// and must declare `tool_calls` (or `tool_use`) for every requested call,
+    /// since the matching `Role::Tool` results sent via [`format_tool_result`](Self::format_tool_result)
+    /// are only valid history alongside a preceding declaration of the calls
+    /// they answer.
+    fn format_assistant_message(&self, text: Option<&str>, tool_calls: &[crate::conversation::FunctionCallRequest]) -> JsonValue;
+}
+
+/// OpenAI-compatible format used by OpenRouter (and OpenAI/AzureOpenAI/Ollama,
+/// which all speak the same `tools`/`tool_calls` shape): schemas pass through
+/// unchanged, and a result becomes a `{"role": "tool", "tool_call_id", "content"}`
+/// message.
+pub struct OpenAiToolFormat;
+
+impl ToolFormat for OpenAiToolFormat {
+    fn build_tools(&self, schemas: &[JsonValue]) -> JsonValue {
+        JsonValue::Array(schemas.to_vec())
+    }
+
+    fn parse_assistant_message(&self, message: &JsonValue) -> ModelTurn {
+        let text = message.get("content").and_then(JsonValue::as_str).map(str::to_string);
+        let function_calls = message
+            .get("tool_calls")
+            .and_then(JsonValue::as_array)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .filter_map(|call| {
+                        let function = call.get("function")?;
+                        let arguments = match function.get("arguments")?.as_str() {
+                            Some(arguments_str) => serde_json::from_str(arguments_str).unwrap_or(JsonValue::Null),
+                            None => function.get("arguments").cloned().unwrap_or(JsonValue::Null),
+                        };
+                        Some(crate::conversation::FunctionCallRequest {
+                            id: call.get("id")?.as_str()?.to_string(),
+                            name: function.get("name")?.as_str()?.to_string(),
+                            arguments,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ModelTurn { text, function_calls }
+    }
+
+    fn format_tool_result(&self, call_id: &str, content: &str, _is_error: bool) -> JsonValue {
+        serde_json::json!({
+            "role": "tool",
+            "tool_call_id": call_id,
+            "content": content,
+        })
+    }
+
+    fn format_assistant_message(&self, text: Option<&str>, tool_calls: &[crate::conversation::FunctionCallRequest]) -> JsonValue {
+        let mut message = serde_json::json!({ "role": "assistant", "content": text });
+
+        if !tool_calls.is_empty() {
+            message["tool_calls"] = JsonValue::Array(
+                tool_calls
+                    .iter()
+                    .map(|call| {
+                        serde_json::json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": { "name": call.name, "arguments": call.arguments.to_string() },
+                        })
+                    })
+                    .collect(),
+            );
+        }
+
+        message
+    }
+}
+
+/// Anthropic Messages API format: a `tools` array of flattened `{"name",
+/// "description", "input_schema"}` entries, assistant calls reported as
+/// `tool_use` content blocks, and results sent back as a `tool_result`
+/// content block inside a `user` message.
+pub struct AnthropicToolFormat;
+
+impl ToolFormat for AnthropicToolFormat {
+    fn build_tools(&self, schemas: &[JsonValue]) -> JsonValue {
+        JsonValue::Array(
+            schemas
+                .iter()
+                .filter_map(|schema| {
+                    let function = schema.get("function")?;
+                    Some(serde_json::json!({
+                        "name": function.get("name")?,
+                        "description": function.get("description").cloned().unwrap_or(JsonValue::String(String::new())),
+                        "input_schema": function.get("parameters").cloned()
+                            .unwrap_or_else(|| serde_json::json!({"type": "object", "properties": {}})),
+                    }))
+                })
+                .collect(),
+        )
+    }
+
+    fn parse_assistant_message(&self, message: &JsonValue) -> ModelTurn {
+        let Some(blocks) = message.get("content").and_then(JsonValue::as_array) else {
+            return ModelTurn::default();
+        };
+
+        let mut text_parts = Vec::new();
+        let mut function_calls = Vec::new();
+
+        for block in blocks {
+            match block.get("type").and_then(JsonValue::as_str) {
+                Some("text") => {
+                    if let Some(text) = block.get("text").and_then(JsonValue::as_str) {
+                        text_parts.push(text.to_string());
+                    }
+                }
+                Some("tool_use") => {
+                    if let (Some(id), Some(name)) = (
+                        block.get("id").and_then(JsonValue::as_str),
+                        block.get("name").and_then(JsonValue::as_str),
+                    ) {
+                        function_calls.push(crate::conversation::FunctionCallRequest {
+                            id: id.to_string(),
+                            name: name.to_string(),
+                            arguments: block.get("input").cloned().unwrap_or(JsonValue::Null),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        ModelTurn {
+            text: (!text_parts.is_empty()).then(|| text_parts.join("\n")),
+            function_calls,
+        }
+    }
+
+    fn format_tool_result(&self, call_id: &str, content: &str, is_error: bool) -> JsonValue {
+        serde_json::json!({
+            "role": "user",
+            "content": [{
+                "type": "tool_result",
+                "tool_use_id": call_id,
+                "content": content,
+                "is_error": is_error,
+            }],
+        })
+    }
+
+    fn format_assistant_message(&self, text: Option<&str>, tool_calls: &[crate::conversation::FunctionCallRequest]) -> JsonValue {
+        let mut blocks = Vec::new();
+
+        if let Some(text) = text {
+            blocks.push(serde_json::json!({ "type": "text", "text": text }));
+        }
+
+        for call in tool_calls {
+            blocks.push(serde_json::json!({
+                "type": "tool_use",
+                "id": call.id,
+                "name": call.name,
+                "input": call.arguments,
+            }));
+        }
+
+        serde_json::json!({ "role": "assistant", "content": blocks })
+    }
+}
+
+/// Returns the [`ToolFormat`] a [`ChatProvider`] speaks. Every provider other
+/// than `Anthropic` speaks the same OpenAI-compatible shape in this crate
+/// today.
+pub fn tool_format_for(provider: ChatProvider) -> Box<dyn ToolFormat> {
+    match provider {
+        ChatProvider::Anthropic => Box::new(AnthropicToolFormat),
+        ChatProvider::OpenAI
+        | ChatProvider::AzureOpenAI
+        | ChatProvider::OpenRouter
+        | ChatProvider::AppleIntelligence
+        | ChatProvider::Ollama => Box::new(OpenAiToolFormat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anthropic_build_tools_flattens_openai_schema() {
+        let schema = serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": "add",
+                "description": "Add two numbers",
+                "parameters": { "type": "object", "properties": { "a": { "type": "integer" } } }
+            }
+        });
+
+        let tools = AnthropicToolFormat.build_tools(&[schema]);
+
+        assert_eq!(tools[0]["name"], serde_json::json!("add"));
+        assert_eq!(tools[0]["input_schema"]["properties"]["a"]["type"], serde_json::json!("integer"));
+    }
+
+    #[test]
+    fn test_anthropic_parse_assistant_message_extracts_tool_use_and_text() {
+        let message = serde_json::json!({
+            "content": [
+                { "type": "text", "text": "Sure, let me calculate that." },
+                { "type": "tool_use", "id": "toolu_1", "name": "add", "input": { "a": 1, "b": 2 } }
+            ]
+        });
+
+        let turn = AnthropicToolFormat.parse_assistant_message(&message);
+
+        assert_eq!(turn.text.as_deref(), Some("Sure, let me calculate that."));
+        assert_eq!(turn.function_calls.len(), 1);
+        assert_eq!(turn.function_calls[0].name, "add");
+        assert_eq!(turn.function_calls[0].id, "toolu_1");
+    }
+
+    #[test]
+    fn test_anthropic_format_tool_result_marks_errors() {
+        let result = AnthropicToolFormat.format_tool_result("toolu_1", "boom", true);
+
+        assert_eq!(result["content"][0]["tool_use_id"], serde_json::json!("toolu_1"));
+        assert_eq!(result["content"][0]["is_error"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_anthropic_format_assistant_message_round_trips_tool_use() {
+        let call = crate::conversation::FunctionCallRequest {
+            id: "toolu_1".to_string(),
+            name: "add".to_string(),
+            arguments: serde_json::json!({ "a": 1, "b": 2 }),
+        };
+
+        let message = AnthropicToolFormat.format_assistant_message(Some("Sure, let me calculate that."), &[call]);
+        let turn = AnthropicToolFormat.parse_assistant_message(&message);
+
+        assert_eq!(turn.text.as_deref(), Some("Sure, let me calculate that."));
+        assert_eq!(turn.function_calls.len(), 1);
+        assert_eq!(turn.function_calls[0].id, "toolu_1");
+    }
+
+    #[test]
+    fn test_openai_format_assistant_message_round_trips_tool_calls() {
+        let call = crate::conversation::FunctionCallRequest {
+            id: "call_1".to_string(),
+            name: "add".to_string(),
+            arguments: serde_json::json!({ "a": 1, "b": 2 }),
+        };
+
+        let message = OpenAiToolFormat.format_assistant_message(None, &[call]);
+        let turn = OpenAiToolFormat.parse_assistant_message(&message);
+
+        assert_eq!(turn.function_calls.len(), 1);
+        assert_eq!(turn.function_calls[0].id, "call_1");
+        assert_eq!(turn.function_calls[0].arguments, serde_json::json!({"a": 1, "b": 2}));
+    }
+
+    #[test]
+    fn test_openai_parse_assistant_message_extracts_tool_calls() {
+        let message = serde_json::json!({
+            "content": "Sure thing",
+            "tool_calls": [
+                { "id": "call_1", "function": { "name": "add", "arguments": "{\"a\":1,\"b\":2}" } }
+            ]
+        });
+
+        let turn = OpenAiToolFormat.parse_assistant_message(&message);
+
+        assert_eq!(turn.text.as_deref(), Some("Sure thing"));
+        assert_eq!(turn.function_calls[0].arguments, serde_json::json!({"a": 1, "b": 2}));
+    }
+}
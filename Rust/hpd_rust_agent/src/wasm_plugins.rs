@@ -0,0 +1,292 @@
+//! Sandboxed WASM plugin runtime.
+//!
+//! Native [`dynamic_plugins`](crate::dynamic_plugins) plugins run with full
+//! host privileges via `dlopen`, which is unacceptable for AI-generated or
+//! third-party function code. This module loads plugins compiled to
+//! `wasm32-wasi` and runs them inside a `wasmtime` sandbox instead, with a
+//! configurable fuel and memory budget per call.
+//!
+//! ## Guest contract
+//!
+//! A WASM plugin exports four functions:
+//!
+//! - `register() -> i64` — packs a pointer/length pair (see [`unpack`]) to a
+//!   UTF-8 JSON string describing `{ "name": ..., "functions": [[name, wrapper], ...],
+//!   "schemas": { name: schema_string, ... } }`.
+//! - `alloc(len: i32) -> i32` — allocates `len` bytes in guest linear memory
+//!   and returns the pointer, so the host can write call arguments in.
+//! - `dealloc(ptr: i32, len: i32)` — frees a buffer previously returned by
+//!   `alloc` or by `call_function`/`register`.
+//! - `call_function(name_ptr, name_len, args_ptr, args_len) -> i64` — invokes
+//!   the named function with a JSON args string and packs a pointer/length
+//!   pair to a JSON string of shape `{ "Ok": ... }` or `{ "Err": ... }`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use wasmtime::{Engine, Instance, Linker, Module, ResourceLimiter, Store, TypedFunc};
+
+use crate::plugins::{register_async_executor, register_plugin, PluginRegistration};
+
+/// Per-call resource limits enforced on a sandboxed plugin invocation.
+#[derive(Debug, Clone, Copy)]
+pub struct WasmPluginLimits {
+    /// Units of fuel available before the guest traps with `OutOfFuel`.
+    pub max_fuel: u64,
+    /// Maximum linear memory, in 64 KiB pages.
+    pub max_memory_pages: u32,
+}
+
+impl Default for WasmPluginLimits {
+    fn default() -> Self {
+        Self {
+            max_fuel: 10_000_000,
+            max_memory_pages: 256, // 16 MiB
+        }
+    }
+}
+
+static ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("failed to initialize wasmtime engine")
+});
+
+/// Store state threaded through every sandboxed instantiation, holding the
+/// [`ResourceLimiter`] that enforces [`WasmPluginLimits::max_memory_pages`].
+/// `Store<()>` can't carry a limiter - `Store::limiter` needs a field on the
+/// store's data to borrow mutably - so every `Store<()>`/`Linker<()>` in this
+/// file is `Store<WasmStoreState>`/`Linker<WasmStoreState>` instead.
+struct WasmStoreState {
+    memory_limiter: WasmMemoryLimiter,
+}
+
+/// Enforces [`WasmPluginLimits::max_memory_pages`] on a sandboxed instance's
+/// linear memory. Returning `Err` (rather than `Ok(false)`, which would just
+/// fail the guest's `memory.grow` with `-1`) traps the instance outright, so
+/// a plugin that tries to allocate past its budget stops immediately instead
+/// of limping along on a `memory.grow` failure it may not check for.
+struct WasmMemoryLimiter {
+    max_bytes: usize,
+}
+
+impl ResourceLimiter for WasmMemoryLimiter {
+    fn memory_growing(&mut self, _current: usize, desired: usize, _maximum: Option<usize>) -> wasmtime::Result<bool> {
+        if desired > self.max_bytes {
+            return Err(wasmtime::Error::msg(format!(
+                "WASM plugin exceeded its {}-byte memory limit (attempted to grow to {} bytes)",
+                self.max_bytes, desired
+            )));
+        }
+        Ok(true)
+    }
+
+    fn table_growing(&mut self, _current: u32, desired: u32, maximum: Option<u32>) -> wasmtime::Result<bool> {
+        Ok(maximum.map_or(true, |max| desired <= max))
+    }
+}
+
+/// Compiled modules, keyed by the module's file path. Compilation is the
+/// expensive part of loading a `.wasm` file, so each module is compiled once
+/// and reused across every instantiation (one per function call).
+static PLUGIN_MODULE_CACHE: Lazy<Mutex<HashMap<String, Module>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(serde::Deserialize)]
+struct WasmPluginManifest {
+    name: String,
+    functions: Vec<(String, String)>,
+    schemas: HashMap<String, String>,
+}
+
+/// Loads a WASM plugin from `path`, registers its functions with the global
+/// plugin registry, and wires each function to execute inside the sandbox.
+///
+/// Returns the plugin's name.
+pub fn load_wasm_plugin(path: &str, limits: WasmPluginLimits) -> Result<String, String> {
+    let module = compiled_module(path)?;
+
+    let manifest = {
+        let mut store = new_store(limits);
+        let instance = instantiate(&module, &mut store)?;
+        call_string_export(&mut store, &instance, "register", &[])?
+    };
+
+    let manifest: WasmPluginManifest = serde_json::from_str(&manifest)
+        .map_err(|e| format!("Invalid register() payload from '{}': {}", path, e))?;
+
+    for (function_name, _wrapper) in &manifest.functions {
+        let path = path.to_string();
+        let function_name_owned = function_name.clone();
+        let limits = limits;
+
+        register_async_executor(
+            function_name.clone(),
+            Box::new(move |args_json| {
+                let path = path.clone();
+                let function_name = function_name_owned.clone();
+                Box::pin(async move { call_wasm_function(&path, &function_name, &args_json, limits) })
+            }),
+        );
+    }
+
+    register_plugin(PluginRegistration {
+        name: manifest.name.clone(),
+        description: format!("Sandboxed WASM plugin loaded from {}", path),
+        functions: manifest.functions,
+        schemas: manifest.schemas,
+        is_unique: true,
+        examples: HashMap::new(),
+    });
+
+    Ok(manifest.name)
+}
+
+/// Invokes `function_name` inside a fresh sandboxed instance of the module
+/// at `path`, passing `args_json` and returning the guest's `Result`-shaped
+/// JSON response.
+fn call_wasm_function(path: &str, function_name: &str, args_json: &str, limits: WasmPluginLimits) -> Result<String, String> {
+    let module = compiled_module(path)?;
+    let mut store = new_store(limits);
+    let instance = instantiate(&module, &mut store)?;
+
+    let name_ptr = write_guest_string(&mut store, &instance, function_name)?;
+    let args_ptr = write_guest_string(&mut store, &instance, args_json)?;
+
+    let call_function: TypedFunc<(i32, i32, i32, i32), i64> = instance
+        .get_typed_func(&mut store, "call_function")
+        .map_err(|e| format!("Plugin at '{}' is missing call_function export: {}", path, e))?;
+
+    let packed = call_function
+        .call(&mut store, (name_ptr.0, name_ptr.1, args_ptr.0, args_ptr.1))
+        .map_err(|e| format!("Plugin '{}' trapped calling '{}': {}", path, function_name, e))?;
+
+    let (ptr, len) = unpack(packed);
+    read_guest_string(&mut store, &instance, ptr, len)
+}
+
+fn compiled_module(path: &str) -> Result<Module, String> {
+    if let Some(module) = PLUGIN_MODULE_CACHE.lock().ok().and_then(|cache| cache.get(path).cloned()) {
+        return Ok(module);
+    }
+
+    let module = Module::from_file(&ENGINE, path)
+        .map_err(|e| format!("Failed to compile WASM plugin '{}': {}", path, e))?;
+
+    if let Ok(mut cache) = PLUGIN_MODULE_CACHE.lock() {
+        cache.insert(path.to_string(), module.clone());
+    }
+
+    Ok(module)
+}
+
+fn new_store(limits: WasmPluginLimits) -> Store<WasmStoreState> {
+    let max_bytes = limits.max_memory_pages as usize * 64 * 1024;
+    let state = WasmStoreState { memory_limiter: WasmMemoryLimiter { max_bytes } };
+
+    let mut store = Store::new(&ENGINE, state);
+    store.limiter(|state| &mut state.memory_limiter);
+    store.set_fuel(limits.max_fuel).expect("fuel consumption is enabled on the engine");
+    store
+}
+
+fn instantiate(module: &Module, store: &mut Store<WasmStoreState>) -> Result<Instance, String> {
+    let linker: Linker<WasmStoreState> = Linker::new(&ENGINE);
+    linker.instantiate(&mut *store, module)
+        .map_err(|e| format!("Failed to instantiate WASM plugin: {}", e))
+}
+
+/// Unpacks a guest pointer/length pair packed the way the guest contract
+/// expects: high 32 bits are the pointer, low 32 bits are the length.
+fn unpack(packed: i64) -> (i32, i32) {
+    ((packed >> 32) as i32, (packed & 0xFFFF_FFFF) as i32)
+}
+
+fn write_guest_string(store: &mut Store<WasmStoreState>, instance: &Instance, value: &str) -> Result<(i32, i32), String> {
+    let alloc: TypedFunc<i32, i32> = instance
+        .get_typed_func(&mut *store, "alloc")
+        .map_err(|e| format!("Plugin is missing alloc export: {}", e))?;
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| "Plugin did not export linear memory".to_string())?;
+
+    let bytes = value.as_bytes();
+    let ptr = alloc.call(&mut *store, bytes.len() as i32)
+        .map_err(|e| format!("Plugin alloc trapped: {}", e))?;
+
+    memory.write(&mut *store, ptr as usize, bytes)
+        .map_err(|e| format!("Failed to write into guest memory: {}", e))?;
+
+    Ok((ptr, bytes.len() as i32))
+}
+
+fn read_guest_string(store: &mut Store<WasmStoreState>, instance: &Instance, ptr: i32, len: i32) -> Result<String, String> {
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| "Plugin did not export linear memory".to_string())?;
+
+    let mut buf = vec![0u8; len as usize];
+    memory.read(&mut *store, ptr as usize, &mut buf)
+        .map_err(|e| format!("Failed to read from guest memory: {}", e))?;
+
+    let result = String::from_utf8(buf)
+        .map_err(|e| format!("Plugin returned non-UTF-8 output: {}", e))?;
+
+    if let Ok(dealloc) = instance.get_typed_func::<(i32, i32), ()>(&mut *store, "dealloc") {
+        let _ = dealloc.call(&mut *store, (ptr, len));
+    }
+
+    Ok(result)
+}
+
+fn call_string_export(store: &mut Store<WasmStoreState>, instance: &Instance, export: &str, args: &[i32]) -> Result<String, String> {
+    debug_assert!(args.is_empty(), "only no-argument string exports are used by this module");
+    let func: TypedFunc<(), i64> = instance
+        .get_typed_func(&mut *store, export)
+        .map_err(|e| format!("Plugin is missing {} export: {}", export, e))?;
+    let packed = func.call(&mut *store, ())
+        .map_err(|e| format!("Plugin trapped calling {}: {}", export, e))?;
+    let (ptr, len) = unpack(packed);
+    read_guest_string(store, instance, ptr, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module that only exports a growable memory - enough to exercise the
+    /// store-level memory limiter directly, without needing the full
+    /// alloc/dealloc/call_function guest contract `load_wasm_plugin` expects.
+    const GROWABLE_MEMORY_WAT: &str = r#"(module (memory (export "memory") 1 100))"#;
+
+    fn growable_memory_module() -> Module {
+        let bytes = wat::parse_str(GROWABLE_MEMORY_WAT).expect("valid WAT");
+        Module::new(&ENGINE, bytes).expect("module should compile")
+    }
+
+    #[test]
+    fn test_memory_limiter_traps_growth_past_max_memory_pages() {
+        let module = growable_memory_module();
+        let limits = WasmPluginLimits { max_fuel: 10_000_000, max_memory_pages: 2 };
+        let mut store = new_store(limits);
+        let instance = instantiate(&module, &mut store).expect("module should instantiate");
+        let memory = instance.get_memory(&mut store, "memory").expect("module exports memory");
+
+        assert!(memory.grow(&mut store, 1).is_ok(), "growing to the page limit should succeed");
+
+        let result = memory.grow(&mut store, 10);
+        assert!(result.is_err(), "growth past max_memory_pages should trap, got {:?}", result);
+    }
+
+    #[test]
+    fn test_memory_limiter_allows_growth_within_max_memory_pages() {
+        let module = growable_memory_module();
+        let limits = WasmPluginLimits { max_fuel: 10_000_000, max_memory_pages: 10 };
+        let mut store = new_store(limits);
+        let instance = instantiate(&module, &mut store).expect("module should instantiate");
+        let memory = instance.get_memory(&mut store, "memory").expect("module exports memory");
+
+        assert!(memory.grow(&mut store, 5).is_ok());
+    }
+}
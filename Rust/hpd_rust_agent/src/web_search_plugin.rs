@@ -0,0 +1,253 @@
+//! WebSearch plugin: structured query primitives over fetched HTML/XML/JSON.
+//!
+//! The dynamic-plugin configuration example
+//! (`examples/plugin_configuration_example.rs`) configures a `WebSearchPlugin`
+//! through `PluginConfiguration` properties (`provider`, `maxResults`,
+//! `enableImageSearch`) but the crate has never shipped the plugin itself.
+//! [`WebSearchPlugin`] fills that gap with query primitives that let the
+//! model extract structured data out of a page it has already fetched,
+//! rather than receiving a raw HTML/XML/JSON blob it has to parse itself:
+//! [`WebSearchPlugin::query_web`] runs a CSS selector over HTML (flattening
+//! `<table>` matches into rows of columns), [`WebSearchPlugin::query_xml`]
+//! resolves a slash-separated path over XML, and
+//! [`WebSearchPlugin::query_json`] resolves a dot-separated path over JSON.
+//! Each returns a normalized [`serde_json::Value`] so the result slots
+//! directly into a tool-call result message.
+
+use scraper::{Html, Selector};
+use serde_json::Value as JsonValue;
+
+use crate::plugin_context::PluginContext;
+use crate::{ai_function, hpd_plugin};
+
+/// Searches the web and extracts structured data from fetched pages.
+/// Configured through the `provider`/`maxResults`/`enableImageSearch`
+/// properties shown in the dynamic-plugin configuration example.
+#[derive(Debug, Clone)]
+pub struct WebSearchPlugin {
+    provider: String,
+    max_results: u32,
+    enable_image_search: bool,
+}
+
+impl Default for WebSearchPlugin {
+    fn default() -> Self {
+        Self {
+            provider: "Tavily".to_string(),
+            max_results: 10,
+            enable_image_search: false,
+        }
+    }
+}
+
+impl WebSearchPlugin {
+    pub fn new(provider: impl Into<String>, max_results: u32, enable_image_search: bool) -> Self {
+        Self { provider: provider.into(), max_results, enable_image_search }
+    }
+
+    /// Builds a plugin from a [`PluginContext`]'s `provider`/`maxResults`/
+    /// `enableImageSearch` properties, falling back to defaults for any
+    /// that are absent.
+    pub fn from_context(context: &PluginContext) -> Self {
+        let defaults = Self::default();
+        Self {
+            provider: context.get_property("provider").unwrap_or(defaults.provider),
+            max_results: context.get_property("maxResults").unwrap_or(defaults.max_results),
+            enable_image_search: context.get_property("enableImageSearch").unwrap_or(defaults.enable_image_search),
+        }
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+}
+
+#[hpd_plugin("WebSearchPlugin", "Searches the web and extracts structured data from fetched pages")]
+impl WebSearchPlugin {
+    #[ai_function(
+        "Run a CSS selector over HTML and return matched nodes as text/attributes, flattening <table> matches into rows of columns",
+        name = "query_web"
+    )]
+    pub fn query_web(&self, html: String, selector: String) -> Result<JsonValue, String> {
+        let parsed_selector = Selector::parse(&selector)
+            .map_err(|e| format!("Invalid CSS selector '{}': {:?}", selector, e))?;
+        let document = Html::parse_document(&html);
+
+        if !self.enable_image_search
+            && document.select(&parsed_selector).any(|element| element.value().name() == "img")
+        {
+            return Err("Image search is disabled for this plugin configuration".to_string());
+        }
+
+        let results: Vec<JsonValue> = document
+            .select(&parsed_selector)
+            .map(|element| {
+                if element.value().name() == "table" {
+                    let rows: Vec<Vec<String>> = element
+                        .select(&row_selector())
+                        .map(|row| {
+                            row.select(&cell_selector())
+                                .map(|cell| cell.text().collect::<String>().trim().to_string())
+                                .collect()
+                        })
+                        .collect();
+                    serde_json::json!({ "table": rows })
+                } else {
+                    let attributes: serde_json::Map<String, JsonValue> = element
+                        .value()
+                        .attrs()
+                        .map(|(name, value)| (name.to_string(), JsonValue::String(value.to_string())))
+                        .collect();
+                    serde_json::json!({
+                        "text": element.text().collect::<String>().trim().to_string(),
+                        "attributes": attributes,
+                    })
+                }
+            })
+            .take(self.max_results as usize)
+            .collect();
+
+        Ok(JsonValue::Array(results))
+    }
+
+    #[ai_function(
+        "Resolve a slash-separated path over XML and return the matched element's text and attributes",
+        name = "query_xml"
+    )]
+    pub fn query_xml(&self, xml: String, path: String) -> Result<JsonValue, String> {
+        let document = roxmltree::Document::parse(&xml).map_err(|e| format!("Invalid XML: {}", e))?;
+        let segments: Vec<&str> = path.trim_start_matches('/').split('/').filter(|s| !s.is_empty()).collect();
+
+        let mut current = document.root_element();
+        for (i, segment) in segments.iter().enumerate() {
+            if i == 0 && current.tag_name().name() == *segment {
+                continue;
+            }
+            current = current
+                .children()
+                .find(|child| child.is_element() && child.tag_name().name() == *segment)
+                .ok_or_else(|| format!("No element found at path '{}'", path))?;
+        }
+
+        let attributes: serde_json::Map<String, JsonValue> = current
+            .attributes()
+            .map(|attr| (attr.name().to_string(), JsonValue::String(attr.value().to_string())))
+            .collect();
+
+        Ok(serde_json::json!({
+            "text": current.text().unwrap_or("").trim(),
+            "attributes": attributes,
+        }))
+    }
+
+    #[ai_function(
+        "Resolve a dot-separated path over JSON (numeric segments index into arrays) and return the matched value",
+        name = "query_json"
+    )]
+    pub fn query_json(&self, json: String, path: String) -> Result<JsonValue, String> {
+        let value: JsonValue = serde_json::from_str(&json).map_err(|e| format!("Invalid JSON: {}", e))?;
+        resolve_json_path(&value, &path)
+            .cloned()
+            .ok_or_else(|| format!("No value found at path '{}'", path))
+    }
+}
+
+fn row_selector() -> Selector {
+    Selector::parse("tr").expect("'tr' is a valid CSS selector")
+}
+
+fn cell_selector() -> Selector {
+    Selector::parse("td, th").expect("'td, th' is a valid CSS selector")
+}
+
+/// Resolves a dot-separated path over a [`JsonValue`], treating a segment
+/// that parses as a `usize` as an array index and anything else as an
+/// object key.
+fn resolve_json_path<'a>(value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    path.split('.').filter(|segment| !segment.is_empty()).try_fold(value, |current, segment| {
+        match segment.parse::<usize>() {
+            Ok(index) => current.get(index),
+            Err(_) => current.get(segment),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_context_falls_back_to_defaults() {
+        let context = PluginContext::new();
+        let plugin = WebSearchPlugin::from_context(&context);
+        assert_eq!(plugin.provider(), "Tavily");
+    }
+
+    #[test]
+    fn test_query_web_extracts_matched_text_and_attributes() {
+        let plugin = WebSearchPlugin::default();
+        let html = r#"<html><body><a href="https://example.com">Example</a></body></html>"#;
+
+        let result = plugin.query_web(html.to_string(), "a".to_string()).unwrap();
+
+        assert_eq!(result[0]["text"], serde_json::json!("Example"));
+        assert_eq!(result[0]["attributes"]["href"], serde_json::json!("https://example.com"));
+    }
+
+    #[test]
+    fn test_query_web_flattens_table_into_rows_of_columns() {
+        let plugin = WebSearchPlugin::default();
+        let html = r#"<table><tr><td>a</td><td>b</td></tr><tr><td>c</td><td>d</td></tr></table>"#;
+
+        let result = plugin.query_web(html.to_string(), "table".to_string()).unwrap();
+
+        assert_eq!(result[0]["table"], serde_json::json!([["a", "b"], ["c", "d"]]));
+    }
+
+    #[test]
+    fn test_query_web_rejects_image_selector_when_disabled() {
+        let plugin = WebSearchPlugin::default();
+        let result = plugin.query_web("<img src='x'>".to_string(), "img".to_string());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_query_web_rejects_compound_selector_matching_images_when_disabled() {
+        let plugin = WebSearchPlugin::default();
+        let html = r#"<html><body><div><img src="x"></div></body></html>"#;
+
+        let result = plugin.query_web(html.to_string(), "div img".to_string());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_query_web_allows_non_image_matches_when_image_search_disabled() {
+        let plugin = WebSearchPlugin::default();
+        let html = r#"<html><body><div><a href="https://example.com">Example</a></div></body></html>"#;
+
+        let result = plugin.query_web(html.to_string(), "div a".to_string()).unwrap();
+
+        assert_eq!(result[0]["text"], serde_json::json!("Example"));
+    }
+
+    #[test]
+    fn test_query_xml_resolves_nested_path() {
+        let plugin = WebSearchPlugin::default();
+        let xml = r#"<root><item id="1"><name>Widget</name></item></root>"#;
+
+        let result = plugin.query_xml(xml.to_string(), "root/item/name".to_string()).unwrap();
+
+        assert_eq!(result["text"], serde_json::json!("Widget"));
+    }
+
+    #[test]
+    fn test_query_json_resolves_dotted_path_with_array_index() {
+        let plugin = WebSearchPlugin::default();
+        let json = r#"{"results": [{"title": "first"}, {"title": "second"}]}"#;
+
+        let result = plugin.query_json(json.to_string(), "results.1.title".to_string()).unwrap();
+
+        assert_eq!(result, serde_json::json!("second"));
+    }
+}
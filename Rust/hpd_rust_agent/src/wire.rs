@@ -0,0 +1,187 @@
+//! Pluggable wire encoding for payloads crossing the FFI boundary.
+//!
+//! `AgentConfig`, the `pending_plugins` vector, and plugin arg/result
+//! payloads in `rust_execute_plugin_function` were all serialized with
+//! `serde_json::to_string`, which is costly for large schemas and
+//! numeric-heavy plugin payloads. [`WireEncoding`] is negotiated per agent
+//! (see [`AgentBuilder::with_encoding`](crate::agent::AgentBuilder::with_encoding))
+//! and recorded against that agent's handle via [`register_encoding`], since
+//! a process can have more than one agent alive at once (a fallback model,
+//! or a `/model`-swapped agent) and each may negotiate a different encoding.
+//! Every later FFI payload for that handle - including the ones
+//! `rust_execute_plugin_function` exchanges with the C# side long after the
+//! agent was built - goes through [`encode`]/[`decode`] keyed off
+//! [`encoding_for_handle`] instead of calling `serde_json` directly. `Json`
+//! stays the default for debuggability; `MessagePack` and `Bincode` give
+//! high-throughput plugins a binary path, base64-encoded so they still fit
+//! through the existing null-terminated `CString` channel.
+
+use std::collections::HashMap;
+use std::os::raw::c_void;
+use std::sync::Mutex;
+
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Which format [`encode`]/[`decode`] use for a payload crossing the FFI
+/// boundary. Mirrors the register-time encoding option (`-e capnp|json`)
+/// mature plugin hosts expose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(into = "u32")]
+#[repr(u32)]
+pub enum WireEncoding {
+    Json = 0,
+    MessagePack = 1,
+    Bincode = 2,
+}
+
+impl Default for WireEncoding {
+    fn default() -> Self {
+        WireEncoding::Json
+    }
+}
+
+impl Into<u32> for WireEncoding {
+    fn into(self) -> u32 {
+        self as u32
+    }
+}
+
+impl TryFrom<u32> for WireEncoding {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(WireEncoding::Json),
+            1 => Ok(WireEncoding::MessagePack),
+            2 => Ok(WireEncoding::Bincode),
+            other => Err(format!("Unknown wire encoding discriminant {}", other)),
+        }
+    }
+}
+
+static ENCODINGS_BY_HANDLE: Lazy<Mutex<HashMap<usize, WireEncoding>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Records which encoding `handle` negotiated, so later FFI payloads for
+/// that specific agent go through the right codec. Called once from
+/// [`AgentBuilder::build`](crate::agent::AgentBuilder::build) with the
+/// encoding negotiated via
+/// [`AgentBuilder::with_encoding`](crate::agent::AgentBuilder::with_encoding),
+/// for both the primary agent and any fallback agent it builds.
+pub fn register_encoding(handle: *mut c_void, encoding: WireEncoding) {
+    if let Ok(mut encodings) = ENCODINGS_BY_HANDLE.lock() {
+        encodings.insert(handle as usize, encoding);
+    }
+}
+
+/// The encoding `handle` negotiated via [`register_encoding`], or
+/// [`WireEncoding::Json`] if `handle` never registered one (e.g. a mock
+/// backend in tests).
+pub fn encoding_for_handle(handle: *mut c_void) -> WireEncoding {
+    ENCODINGS_BY_HANDLE
+        .lock()
+        .ok()
+        .and_then(|encodings| encodings.get(&(handle as usize)).copied())
+        .unwrap_or(WireEncoding::Json)
+}
+
+/// Forgets `handle`'s negotiated encoding. Called when an [`Agent`](crate::agent::Agent)
+/// is dropped so the registry doesn't grow unboundedly across repeated
+/// `/model` swaps or short-lived agents.
+pub fn unregister_encoding(handle: *mut c_void) {
+    if let Ok(mut encodings) = ENCODINGS_BY_HANDLE.lock() {
+        encodings.remove(&(handle as usize));
+    }
+}
+
+/// Serializes `value` with `encoding` into a string suitable for handing
+/// across the FFI boundary as a `CString`. Binary encodings are
+/// base64-wrapped so they stay free of interior null bytes.
+pub fn encode<T: Serialize>(value: &T, encoding: WireEncoding) -> Result<String, String> {
+    match encoding {
+        WireEncoding::Json => serde_json::to_string(value).map_err(|e| format!("JSON encode failed: {}", e)),
+        WireEncoding::MessagePack => {
+            let bytes = rmp_serde::to_vec(value).map_err(|e| format!("MessagePack encode failed: {}", e))?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+        }
+        WireEncoding::Bincode => {
+            let bytes = bincode::serialize(value).map_err(|e| format!("Bincode encode failed: {}", e))?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+        }
+    }
+}
+
+/// Inverse of [`encode`]: decodes `data` as `encoding`, reversing the
+/// base64 wrapping for binary encodings first.
+pub fn decode<T: DeserializeOwned>(data: &str, encoding: WireEncoding) -> Result<T, String> {
+    match encoding {
+        WireEncoding::Json => serde_json::from_str(data).map_err(|e| format!("JSON decode failed: {}", e)),
+        WireEncoding::MessagePack => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("Base64 decode failed: {}", e))?;
+            rmp_serde::from_slice(&bytes).map_err(|e| format!("MessagePack decode failed: {}", e))
+        }
+        WireEncoding::Bincode => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("Base64 decode failed: {}", e))?;
+            bincode::deserialize(&bytes).map_err(|e| format!("Bincode decode failed: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+    struct Sample {
+        name: String,
+        count: u32,
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let sample = Sample { name: "widget".to_string(), count: 3 };
+        let encoded = encode(&sample, WireEncoding::Json).unwrap();
+        assert_eq!(decode::<Sample>(&encoded, WireEncoding::Json).unwrap(), sample);
+    }
+
+    #[test]
+    fn test_message_pack_roundtrip() {
+        let sample = Sample { name: "widget".to_string(), count: 3 };
+        let encoded = encode(&sample, WireEncoding::MessagePack).unwrap();
+        assert_eq!(decode::<Sample>(&encoded, WireEncoding::MessagePack).unwrap(), sample);
+    }
+
+    #[test]
+    fn test_bincode_roundtrip() {
+        let sample = Sample { name: "widget".to_string(), count: 3 };
+        let encoded = encode(&sample, WireEncoding::Bincode).unwrap();
+        assert_eq!(decode::<Sample>(&encoded, WireEncoding::Bincode).unwrap(), sample);
+    }
+
+    #[test]
+    fn test_encoding_for_handle_defaults_to_json() {
+        let handle = 0x1234 as *mut c_void;
+        assert_eq!(encoding_for_handle(handle), WireEncoding::Json);
+    }
+
+    #[test]
+    fn test_register_encoding_is_per_handle() {
+        let handle_a = 0x1 as *mut c_void;
+        let handle_b = 0x2 as *mut c_void;
+
+        register_encoding(handle_a, WireEncoding::MessagePack);
+        register_encoding(handle_b, WireEncoding::Bincode);
+
+        assert_eq!(encoding_for_handle(handle_a), WireEncoding::MessagePack);
+        assert_eq!(encoding_for_handle(handle_b), WireEncoding::Bincode);
+
+        unregister_encoding(handle_a);
+        unregister_encoding(handle_b);
+    }
+}